@@ -1,6 +1,9 @@
 use alloc::boxed::Box;
 
-use vexide::prelude::*;
+use crate::{
+    float::Float,
+    hal::{MotorGroup, MotorTarget},
+};
 
 // NOTE: do i really need to be doing an allocation for state every 10 ms update?
 // convenience of isolating the update implementations
@@ -9,8 +12,8 @@ use vexide::prelude::*;
 const LIFT_THRESHOLD: f64 = 4.0;
 const WRIST_THRESHOLD: f64 = 3.0;
 
-const LIFT_VEL: i32 = 200;
-const WRIST_VEL: i32 = 70;
+const LIFT_VEL: f64 = 200.0;
+const WRIST_VEL: f64 = 70.0;
 
 const ACCEPT_LIFT_POS: f64 = 390.0;
 const ACCEPT_WRIST_POS: f64 = -130.0;
@@ -34,45 +37,34 @@ pub enum ArmSignal {
 
 // ! change this to take from the motor target
 
-fn motor_ready(mtr: &Motor, thres: f64) -> bool {
-    let target: MotorControl = mtr
-        .target()
-        .unwrap_or(MotorControl::Brake(BrakeMode::Brake));
-    match target {
-        MotorControl::Brake(_) => true,
-        MotorControl::Position(pos, _) => {
-            (mtr.position().unwrap_or_default() - pos)
-                .as_degrees()
-                .abs()
-                < thres
-        }
+fn motor_ready<M: MotorGroup>(mtr: &M, thres: f64) -> bool {
+    match mtr.target() {
+        MotorTarget::Brake(_) => true,
+        MotorTarget::Position(pos, _) => Float::abs(mtr.position() - pos) < thres,
         _ => false,
     }
 }
 
-fn arm_move(
-    lift: &mut Motor,
+fn arm_move<M: MotorGroup>(
+    lift: &mut M,
     lift_deg: f64,
-    lift_vel: i32,
-    wrist: &mut Motor,
+    lift_vel: f64,
+    wrist: &mut M,
     wrist_deg: f64,
-    wrist_vel: i32,
+    wrist_vel: f64,
 ) {
-    lift.set_position_target(Position::from_degrees(lift_deg), lift_vel)
-        .ok();
-    wrist
-        .set_position_target(Position::from_degrees(wrist_deg), wrist_vel)
-        .ok();
+    lift.set_position_target(lift_deg, lift_vel);
+    wrist.set_position_target(wrist_deg, wrist_vel);
 }
 
-pub struct Arm {
-    state: Option<Box<dyn ArmState>>,
-    lift: Motor,
-    wrist: Motor,
+pub struct Arm<M: MotorGroup> {
+    state: Option<Box<dyn ArmState<M>>>,
+    lift: M,
+    wrist: M,
 }
 
-impl Arm {
-    pub fn new(lift: Motor, wrist: Motor) -> Self {
+impl<M: MotorGroup> Arm<M> {
+    pub fn new(lift: M, wrist: M) -> Self {
         Arm {
             state: Some(Box::new(Returning {})),
             lift,
@@ -98,35 +90,31 @@ impl Arm {
     }
 }
 
-trait ArmState {
-    fn act(&self, lift: &mut Motor, wrist: &mut Motor);
-    fn update(self: Box<Self>, lift: &Motor, wrist: &Motor, signal: ArmSignal)
-        -> Box<dyn ArmState>;
+trait ArmState<M: MotorGroup> {
+    fn act(&self, lift: &mut M, wrist: &mut M);
+    fn update(self: Box<Self>, lift: &M, wrist: &M, signal: ArmSignal) -> Box<dyn ArmState<M>>;
     fn name(&self) -> &str;
 }
 
 struct Returning {}
 
-impl ArmState for Returning {
-    fn act(&self, lift: &mut Motor, wrist: &mut Motor) {
-        if wrist
-            .position()
-            .unwrap_or(Position::from_degrees(0.0))
-            .as_degrees()
-            <= 0.0
-        {
-            arm_move(lift, ACCEPT_LIFT_POS, 200, wrist, ACCEPT_WRIST_POS, 100);
+impl<M: MotorGroup> ArmState<M> for Returning {
+    fn act(&self, lift: &mut M, wrist: &mut M) {
+        if wrist.position() <= 0.0 {
+            arm_move(lift, ACCEPT_LIFT_POS, 200.0, wrist, ACCEPT_WRIST_POS, 100.0);
         } else {
-            arm_move(lift, RELEASE_LIFT_POS, 200, wrist, ACCEPT_WRIST_POS, 100);
+            arm_move(
+                lift,
+                RELEASE_LIFT_POS,
+                200.0,
+                wrist,
+                ACCEPT_WRIST_POS,
+                100.0,
+            );
         }
     }
 
-    fn update(
-        self: Box<Self>,
-        lift: &Motor,
-        wrist: &Motor,
-        _signal: ArmSignal,
-    ) -> Box<dyn ArmState> {
+    fn update(self: Box<Self>, lift: &M, wrist: &M, _signal: ArmSignal) -> Box<dyn ArmState<M>> {
         let lift_ready = motor_ready(lift, LIFT_THRESHOLD);
         let wrist_ready = motor_ready(wrist, WRIST_THRESHOLD);
         if lift_ready && wrist_ready {
@@ -143,8 +131,8 @@ impl ArmState for Returning {
 
 struct Accepting {}
 
-impl ArmState for Accepting {
-    fn act(&self, lift: &mut Motor, wrist: &mut Motor) {
+impl<M: MotorGroup> ArmState<M> for Accepting {
+    fn act(&self, lift: &mut M, wrist: &mut M) {
         arm_move(
             lift,
             ACCEPT_LIFT_POS,
@@ -155,12 +143,7 @@ impl ArmState for Accepting {
         );
     }
 
-    fn update(
-        self: Box<Self>,
-        _lift: &Motor,
-        _wrist: &Motor,
-        signal: ArmSignal,
-    ) -> Box<dyn ArmState> {
+    fn update(self: Box<Self>, _lift: &M, _wrist: &M, signal: ArmSignal) -> Box<dyn ArmState<M>> {
         match signal {
             ArmSignal::Score => Box::new(Ready {}),
             _ => self,
@@ -174,8 +157,8 @@ impl ArmState for Accepting {
 
 struct Ready {}
 
-impl ArmState for Ready {
-    fn act(&self, lift: &mut Motor, wrist: &mut Motor) {
+impl<M: MotorGroup> ArmState<M> for Ready {
+    fn act(&self, lift: &mut M, wrist: &mut M) {
         arm_move(
             lift,
             READY_LIFT_POS,
@@ -186,12 +169,7 @@ impl ArmState for Ready {
         );
     }
 
-    fn update(
-        self: Box<Self>,
-        _lift: &Motor,
-        wrist: &Motor,
-        _signal: ArmSignal,
-    ) -> Box<dyn ArmState> {
+    fn update(self: Box<Self>, _lift: &M, wrist: &M, _signal: ArmSignal) -> Box<dyn ArmState<M>> {
         let lift_ready = true; // motor_ready(lift, LIFT_THRESHOLD + 20.0);
         let wrist_ready = motor_ready(wrist, WRIST_THRESHOLD + 30.0);
         if lift_ready && wrist_ready {
@@ -208,8 +186,8 @@ impl ArmState for Ready {
 
 struct Scoring {}
 
-impl ArmState for Scoring {
-    fn act(&self, lift: &mut Motor, wrist: &mut Motor) {
+impl<M: MotorGroup> ArmState<M> for Scoring {
+    fn act(&self, lift: &mut M, wrist: &mut M) {
         arm_move(
             lift,
             SCORE_LIFT_POS,
@@ -220,12 +198,7 @@ impl ArmState for Scoring {
         );
     }
 
-    fn update(
-        self: Box<Self>,
-        lift: &Motor,
-        wrist: &Motor,
-        signal: ArmSignal,
-    ) -> Box<dyn ArmState> {
+    fn update(self: Box<Self>, lift: &M, wrist: &M, signal: ArmSignal) -> Box<dyn ArmState<M>> {
         if matches!(signal, ArmSignal::Return) {
             return Box::new(Returning {});
         }
@@ -246,21 +219,16 @@ impl ArmState for Scoring {
 
 struct Releasing {}
 
-impl ArmState for Releasing {
-    fn act(&self, lift: &mut Motor, wrist: &mut Motor) {
-        if lift
-            .position()
-            .unwrap_or(Position::from_degrees(0.0))
-            .as_degrees()
-            <= 590.0
-        {
+impl<M: MotorGroup> ArmState<M> for Releasing {
+    fn act(&self, lift: &mut M, wrist: &mut M) {
+        if lift.position() <= 590.0 {
             arm_move(
                 lift,
                 RELEASE_LIFT_POS,
                 LIFT_VEL,
                 wrist,
                 SCORE_WRIST_POS,
-                100,
+                100.0,
             );
         } else {
             arm_move(
@@ -269,17 +237,12 @@ impl ArmState for Releasing {
                 LIFT_VEL,
                 wrist,
                 RELEASE_WRIST_POS,
-                100,
+                100.0,
             );
         }
     }
 
-    fn update(
-        self: Box<Self>,
-        lift: &Motor,
-        wrist: &Motor,
-        _signal: ArmSignal,
-    ) -> Box<dyn ArmState> {
+    fn update(self: Box<Self>, lift: &M, wrist: &M, _signal: ArmSignal) -> Box<dyn ArmState<M>> {
         let lift_ready = motor_ready(lift, LIFT_THRESHOLD + 5.0);
         let wrist_ready = motor_ready(wrist, WRIST_THRESHOLD + 10.0);
         if lift_ready && wrist_ready {
@@ -3,47 +3,89 @@
 
 #[macro_use]
 extern crate alloc;
-mod arm;
-mod localisation;
-mod piston;
-mod tank_chassis;
 
 use alloc::{
     string::{String, ToString},
     sync::Arc,
+    vec::Vec,
 };
 use core::time::Duration;
 
-use vexide::{
-    core::{sync::Mutex, time::Instant},
-    devices::screen::*,
-    prelude::*,
+use lib3818_rs::{
+    arm::*,
+    localisation::{
+        heading::TrackerHeading,
+        shared::{LocalisationRunner, SharedPose},
+        *,
+    },
+    pid::{Pid, Settle},
+    piston::Piston,
+    rt::{sleep, sleep_until, Instant, Mutex},
+    tank_chassis::{
+        commands::{ChassisCommands, CommandSettings, TurnDirection},
+        TankChassis,
+    },
+    units::Length,
 };
+use vexide::{devices::screen::*, prelude::*};
 
-use crate::{arm::*, localisation::*, piston::Piston, tank_chassis::TankChassis};
+const TRACK_WIDTH: Length = Length::from_inches(10.0);
+const DRIVE_WHEEL_DIAMETER: Length = Length::from_inches(3.25);
+const TRACKING_WHEEL_DIAMETER: Length = Length::from_inches(2.0);
+
+type Odometry = TrackingWheelLocaliser<
+    TrackerAxisWheel<RotationSensor>,
+    TrackerAxisDrive<Vec<Motor>>,
+    TrackerHeading<TrackerAxisDrive<Vec<Motor>>, TrackerAxisDrive<Vec<Motor>>>,
+>;
 
 struct Robot {
     scr: Screen,
     controller: Controller,
-    chassis: Arc<Mutex<TankChassis>>,
+    chassis: Arc<Mutex<TankChassis<Vec<Motor>>>>,
 
     intake: Motor,
 
-    arm: Arm,
-    clamp: Piston,
+    arm: Arm<Motor>,
+    clamp: Piston<AdiDigitalOut>,
     distance_cage: DistanceSensor,
 
-    localiser: TrackingWheelLocaliser<TrackerAxisWheel, TrackerAxisDrive>,
+    localiser: SharedPose,
+    // localisation stops when the task is dropped
+    _odometry: Task<()>,
 }
 
 impl Compete for Robot {
     async fn autonomous(&mut self) {
         println!("Autonomous!");
-        self.localiser
-            .set_pose(Pose::new(0.0, 0.0, Heading::new(0.0)));
-        self.chassis.lock().await.move_tank(1.0, 1.0);
-        sleep(Duration::from_secs_f32(0.5)).await;
-        self.chassis.lock().await.brake(BrakeMode::Brake);
+        self.localiser.set_pose(Pose::identity()).await;
+
+        let mut drive = CommandSettings::new(
+            Pid::new(0.005, 0.0, 0.0003),
+            Settle::new(10.0, 20.0, Duration::from_millis(200)),
+            Duration::from_secs(2),
+        );
+        let mut turn = CommandSettings::new(
+            Pid::new(0.8, 0.0, 0.05),
+            Settle::new(
+                1.5_f64.to_radians(),
+                10.0_f64.to_radians(),
+                Duration::from_millis(200),
+            ),
+            Duration::from_secs(2),
+        );
+
+        self.chassis
+            .drive_distance(&mut self.localiser, 600.0, &mut drive)
+            .await;
+        self.chassis
+            .turn_to_heading(
+                &mut self.localiser,
+                Heading::from_deg(0.0, AngleSystem::Bearing),
+                TurnDirection::Shortest,
+                &mut turn,
+            )
+            .await;
     }
 
     async fn driver(&mut self) {
@@ -54,10 +96,6 @@ impl Compete for Robot {
         loop {
             let time_start = Instant::now();
 
-            // sensor updates
-            // TODO: move to task
-            self.localiser.update().await;
-
             // drive the intake using right triggers
             // block the intake if cage is full
             if self
@@ -130,7 +168,7 @@ impl Compete for Robot {
 
             // TODO: get better string concatenation
             // display position
-            let pose = self.localiser.pose();
+            let pose = self.localiser.pose().await;
             let obj = Text::new(
                 (String::from("pose: ") + &pose.to_string()).as_str(),
                 TextSize::Small,
@@ -171,14 +209,35 @@ async fn main(peripherals: Peripherals) {
     odom_x.set_data_rate(Duration::from_millis(5)).ok();
 
     let chassis = Arc::new(Mutex::new(TankChassis::new(
-        m_l1, m_l2, m_lt, m_r1, m_r2, m_rt,
+        vec![m_l1, m_l2, m_lt],
+        vec![m_r1, m_r2, m_rt],
     )));
 
-    let localiser = TrackingWheelLocaliser::from_chassis_and_wheel(
-        TrackerAxisWheel::new(odom_x, 0.0),
-        TrackerAxisDrive::new(chassis.clone(), 254.0),
-        Pose::new(0.0, 0.0, Heading::new(0.0)),
+    let odometry: Odometry = TrackingWheelLocaliser::new(
+        TrackerAxisWheel::new(odom_x, Length::ZERO, TRACKING_WHEEL_DIAMETER),
+        TrackerAxisDrive::new(
+            chassis.clone(),
+            ChassisSide::Right,
+            TRACK_WIDTH,
+            DRIVE_WHEEL_DIAMETER,
+        ),
+        TrackerHeading::new(
+            TrackerAxisDrive::new(
+                chassis.clone(),
+                ChassisSide::Left,
+                TRACK_WIDTH,
+                DRIVE_WHEEL_DIAMETER,
+            ),
+            TrackerAxisDrive::new(
+                chassis.clone(),
+                ChassisSide::Right,
+                TRACK_WIDTH,
+                DRIVE_WHEEL_DIAMETER,
+            ),
+        ),
+        Pose::identity(),
     );
+    let odometry = LocalisationRunner::new(odometry, Duration::from_millis(10));
 
     let mut master = peripherals.primary_controller;
     let scr = peripherals.screen;
@@ -189,7 +248,8 @@ async fn main(peripherals: Peripherals) {
         scr,
         controller: master,
         chassis,
-        localiser,
+        localiser: odometry.handle(),
+        _odometry: odometry.spawn(),
         intake,
         arm: Arm::new(m_h_lift, m_wrist),
         clamp: Piston::new(adi_clamp, false),
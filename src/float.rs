@@ -0,0 +1,126 @@
+//! Floating point functions missing from `core`.
+//!
+//! Backed by `libm` so results are the same on the brain and on the host.
+
+use libm::Libm;
+
+pub trait Float: Sized {
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn sqrt(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+}
+
+macro_rules! impl_float {
+    ($t:ty) => {
+        impl Float for $t {
+            fn abs(self) -> Self {
+                Libm::<$t>::fabs(self)
+            }
+
+            fn signum(self) -> Self {
+                if self.is_nan() {
+                    self
+                } else {
+                    Libm::<$t>::copysign(1.0, self)
+                }
+            }
+
+            fn floor(self) -> Self {
+                Libm::<$t>::floor(self)
+            }
+
+            fn ceil(self) -> Self {
+                Libm::<$t>::ceil(self)
+            }
+
+            fn round(self) -> Self {
+                Libm::<$t>::round(self)
+            }
+
+            fn rem_euclid(self, rhs: Self) -> Self {
+                let r = Libm::<$t>::fmod(self, rhs);
+                if r < 0.0 {
+                    r + rhs.abs()
+                } else {
+                    r
+                }
+            }
+
+            fn powi(self, n: i32) -> Self {
+                Libm::<$t>::pow(self, n as $t)
+            }
+
+            fn sqrt(self) -> Self {
+                Libm::<$t>::sqrt(self)
+            }
+
+            fn cbrt(self) -> Self {
+                Libm::<$t>::cbrt(self)
+            }
+
+            fn hypot(self, other: Self) -> Self {
+                Libm::<$t>::hypot(self, other)
+            }
+
+            fn exp(self) -> Self {
+                Libm::<$t>::exp(self)
+            }
+
+            fn ln(self) -> Self {
+                Libm::<$t>::log(self)
+            }
+
+            fn sin(self) -> Self {
+                Libm::<$t>::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                Libm::<$t>::cos(self)
+            }
+
+            fn tan(self) -> Self {
+                Libm::<$t>::tan(self)
+            }
+
+            fn asin(self) -> Self {
+                Libm::<$t>::asin(self)
+            }
+
+            fn acos(self) -> Self {
+                Libm::<$t>::acos(self)
+            }
+
+            fn atan(self) -> Self {
+                Libm::<$t>::atan(self)
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                Libm::<$t>::atan2(self, other)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                Libm::<$t>::sincos(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
@@ -0,0 +1,180 @@
+//! In-memory devices for running the library on the host.
+//!
+//! Clones share state, so a test or simulator can keep a handle to read commands
+//! and set readings after the device is moved into a chassis or localiser.
+
+use alloc::rc::Rc;
+use core::cell::{Cell, RefCell};
+
+use crate::hal::{
+    BrakeMode, DigitalOut, DistanceReading, DistanceSensor, Imu, MotorGroup, MotorTarget,
+    RotaryEncoder,
+};
+
+struct MockMotorState {
+    target: MotorTarget,
+    position: f64,
+    velocity: f64,
+    max_rpm: f64,
+}
+
+/// Records the last command and reports whatever position and velocity it is given
+#[derive(Clone)]
+pub struct MockMotor {
+    state: Rc<RefCell<MockMotorState>>,
+}
+
+impl MockMotor {
+    pub fn new(max_rpm: f64) -> Self {
+        MockMotor {
+            state: Rc::new(RefCell::new(MockMotorState {
+                target: MotorTarget::Brake(BrakeMode::Coast),
+                position: 0.0,
+                velocity: 0.0,
+                max_rpm,
+            })),
+        }
+    }
+
+    /// sets the position (degrees) and velocity (rpm) the motor reports
+    pub fn set_measured(&self, position: f64, velocity: f64) {
+        let mut state = self.state.borrow_mut();
+        state.position = position;
+        state.velocity = velocity;
+    }
+}
+
+impl MotorGroup for MockMotor {
+    fn set_voltage(&mut self, volts: f64) {
+        self.state.borrow_mut().target = MotorTarget::Voltage(volts);
+    }
+
+    fn set_velocity(&mut self, rpm: f64) {
+        self.state.borrow_mut().target = MotorTarget::Velocity(rpm);
+    }
+
+    fn set_position_target(&mut self, deg: f64, rpm: f64) {
+        self.state.borrow_mut().target = MotorTarget::Position(deg, rpm);
+    }
+
+    fn brake(&mut self, mode: BrakeMode) {
+        self.state.borrow_mut().target = MotorTarget::Brake(mode);
+    }
+
+    fn target(&self) -> MotorTarget {
+        self.state.borrow().target
+    }
+
+    fn position(&self) -> f64 {
+        self.state.borrow().position
+    }
+
+    fn velocity(&self) -> f64 {
+        self.state.borrow().velocity
+    }
+
+    fn max_rpm(&self) -> f64 {
+        self.state.borrow().max_rpm
+    }
+}
+
+impl RotaryEncoder for MockMotor {
+    fn position(&self) -> f64 {
+        MotorGroup::position(self)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MockEncoder {
+    position: Rc<Cell<f64>>,
+}
+
+impl MockEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// degrees
+    pub fn set_position(&self, position: f64) {
+        self.position.set(position);
+    }
+}
+
+impl RotaryEncoder for MockEncoder {
+    fn position(&self) -> f64 {
+        self.position.get()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MockImu {
+    rotation: Rc<Cell<Option<f64>>>,
+    calibrating: Rc<Cell<bool>>,
+}
+
+impl MockImu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// degrees clockwise
+    pub fn set_rotation(&self, rotation: Option<f64>) {
+        self.rotation.set(rotation);
+    }
+
+    pub fn set_calibrating(&self, calibrating: bool) {
+        self.calibrating.set(calibrating);
+    }
+}
+
+impl Imu for MockImu {
+    fn rotation(&self) -> Option<f64> {
+        self.rotation.get()
+    }
+
+    fn is_calibrating(&self) -> bool {
+        self.calibrating.get()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MockDigitalOut {
+    high: Rc<Cell<bool>>,
+}
+
+impl MockDigitalOut {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_high(&self) -> bool {
+        self.high.get()
+    }
+}
+
+impl DigitalOut for MockDigitalOut {
+    fn set_level(&mut self, high: bool) {
+        self.high.set(high);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MockDistanceSensor {
+    object: Rc<Cell<Option<DistanceReading>>>,
+}
+
+impl MockDistanceSensor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_object(&self, object: Option<DistanceReading>) {
+        self.object.set(object);
+    }
+}
+
+impl DistanceSensor for MockDistanceSensor {
+    fn object(&self) -> Option<DistanceReading> {
+        self.object.get()
+    }
+}
@@ -0,0 +1,131 @@
+//! Device traits used by the rest of the library.
+//!
+//! vexide devices implement these with the `vexide` feature, and [`mock`] has
+//! in-memory versions for running on the host.
+
+pub mod mock;
+#[cfg(feature = "vexide")]
+mod v5;
+
+use alloc::vec::Vec;
+
+pub const MAX_VOLTAGE: f64 = 12.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BrakeMode {
+    Coast,
+    Brake,
+    Hold,
+}
+
+/// Last command given to a motor
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MotorTarget {
+    Brake(BrakeMode),
+    /// volts
+    Voltage(f64),
+    /// rpm
+    Velocity(f64),
+    /// degrees, at a maximum rpm
+    Position(f64, f64),
+}
+
+/// One or more motors driven together
+pub trait MotorGroup {
+    fn set_voltage(&mut self, volts: f64);
+    fn set_velocity(&mut self, rpm: f64);
+    fn set_position_target(&mut self, deg: f64, rpm: f64);
+    fn brake(&mut self, mode: BrakeMode);
+    fn target(&self) -> MotorTarget;
+    /// degrees, averaged across the group
+    fn position(&self) -> f64;
+    /// rpm, averaged across the group
+    fn velocity(&self) -> f64;
+    fn max_rpm(&self) -> f64;
+}
+
+impl<M: MotorGroup> MotorGroup for Vec<M> {
+    fn set_voltage(&mut self, volts: f64) {
+        for motor in self.iter_mut() {
+            motor.set_voltage(volts);
+        }
+    }
+
+    fn set_velocity(&mut self, rpm: f64) {
+        for motor in self.iter_mut() {
+            motor.set_velocity(rpm);
+        }
+    }
+
+    fn set_position_target(&mut self, deg: f64, rpm: f64) {
+        for motor in self.iter_mut() {
+            motor.set_position_target(deg, rpm);
+        }
+    }
+
+    fn brake(&mut self, mode: BrakeMode) {
+        for motor in self.iter_mut() {
+            motor.brake(mode);
+        }
+    }
+
+    fn target(&self) -> MotorTarget {
+        self.first()
+            .map_or(MotorTarget::Brake(BrakeMode::Coast), |motor| motor.target())
+    }
+
+    fn position(&self) -> f64 {
+        let sum: f64 = self.iter().map(|motor| motor.position()).sum();
+        sum / self.len() as f64
+    }
+
+    fn velocity(&self) -> f64 {
+        let sum: f64 = self.iter().map(|motor| motor.velocity()).sum();
+        sum / self.len() as f64
+    }
+
+    // the slowest motor limits the group
+    fn max_rpm(&self) -> f64 {
+        self.iter()
+            .map(|motor| motor.max_rpm())
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+pub trait RotaryEncoder {
+    /// degrees
+    fn position(&self) -> f64;
+}
+
+pub trait Imu {
+    /// degrees clockwise, continuing past full turns, `None` while calibrating or
+    /// disconnected
+    fn rotation(&self) -> Option<f64>;
+    fn is_calibrating(&self) -> bool;
+}
+
+pub trait DigitalOut {
+    fn set_level(&mut self, high: bool);
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DistanceReading {
+    /// mm
+    pub distance: f64,
+    /// 0 to 1
+    pub confidence: f64,
+    /// apparent size of the object, larger for objects that fill more of the view
+    pub relative_size: u32,
+}
+
+pub trait DistanceSensor {
+    /// closest object in view, if any
+    fn object(&self) -> Option<DistanceReading>;
+}
+
+// so sensors owned elsewhere can be borrowed for a reading
+impl<S: DistanceSensor> DistanceSensor for &S {
+    fn object(&self) -> Option<DistanceReading> {
+        (**self).object()
+    }
+}
@@ -0,0 +1,116 @@
+use vexide::{
+    devices::smart::DistanceSensor as V5DistanceSensor,
+    prelude::{AdiDigitalOut, InertialSensor, Motor, MotorControl, Position, RotationSensor},
+};
+
+use crate::hal::{
+    BrakeMode, DigitalOut, DistanceReading, DistanceSensor, Imu, MotorGroup, MotorTarget,
+    RotaryEncoder,
+};
+
+impl From<BrakeMode> for vexide::prelude::BrakeMode {
+    fn from(mode: BrakeMode) -> Self {
+        match mode {
+            BrakeMode::Coast => vexide::prelude::BrakeMode::Coast,
+            BrakeMode::Brake => vexide::prelude::BrakeMode::Brake,
+            BrakeMode::Hold => vexide::prelude::BrakeMode::Hold,
+        }
+    }
+}
+
+impl From<vexide::prelude::BrakeMode> for BrakeMode {
+    fn from(mode: vexide::prelude::BrakeMode) -> Self {
+        match mode {
+            vexide::prelude::BrakeMode::Coast => BrakeMode::Coast,
+            vexide::prelude::BrakeMode::Brake => BrakeMode::Brake,
+            vexide::prelude::BrakeMode::Hold => BrakeMode::Hold,
+        }
+    }
+}
+
+impl MotorGroup for Motor {
+    fn set_voltage(&mut self, volts: f64) {
+        Motor::set_voltage(self, volts).ok();
+    }
+
+    fn set_velocity(&mut self, rpm: f64) {
+        Motor::set_velocity(self, rpm as i32).ok();
+    }
+
+    fn set_position_target(&mut self, deg: f64, rpm: f64) {
+        Motor::set_position_target(self, Position::from_degrees(deg), rpm as i32).ok();
+    }
+
+    fn brake(&mut self, mode: BrakeMode) {
+        Motor::brake(self, mode.into()).ok();
+    }
+
+    fn target(&self) -> MotorTarget {
+        match Motor::target(self).unwrap_or(MotorControl::Brake(vexide::prelude::BrakeMode::Brake))
+        {
+            MotorControl::Brake(mode) => MotorTarget::Brake(mode.into()),
+            MotorControl::Voltage(volts) => MotorTarget::Voltage(volts),
+            MotorControl::Velocity(rpm) => MotorTarget::Velocity(rpm as f64),
+            MotorControl::Position(pos, rpm) => MotorTarget::Position(pos.as_degrees(), rpm as f64),
+        }
+    }
+
+    fn position(&self) -> f64 {
+        Motor::position(self).unwrap_or_default().as_degrees()
+    }
+
+    fn velocity(&self) -> f64 {
+        Motor::velocity(self).unwrap_or(0) as f64
+    }
+
+    fn max_rpm(&self) -> f64 {
+        self.gearset().map_or(0.0, |gearset| gearset.max_rpm())
+    }
+}
+
+impl RotaryEncoder for RotationSensor {
+    fn position(&self) -> f64 {
+        RotationSensor::position(self)
+            .unwrap_or(Position::from_degrees(0.0))
+            .as_degrees()
+    }
+}
+
+impl RotaryEncoder for Motor {
+    fn position(&self) -> f64 {
+        MotorGroup::position(self)
+    }
+}
+
+impl Imu for InertialSensor {
+    fn rotation(&self) -> Option<f64> {
+        // errors while calibrating
+        InertialSensor::rotation(self).ok()
+    }
+
+    fn is_calibrating(&self) -> bool {
+        InertialSensor::is_calibrating(self).unwrap_or(false)
+    }
+}
+
+impl DigitalOut for AdiDigitalOut {
+    fn set_level(&mut self, high: bool) {
+        if high {
+            self.set_high().ok();
+        } else {
+            self.set_low().ok();
+        }
+    }
+}
+
+impl DistanceSensor for V5DistanceSensor {
+    fn object(&self) -> Option<DistanceReading> {
+        V5DistanceSensor::object(self)
+            .unwrap_or(None)
+            .map(|object| DistanceReading {
+                distance: object.distance as f64,
+                confidence: object.confidence,
+                relative_size: object.relative_size,
+            })
+    }
+}
@@ -0,0 +1,27 @@
+//! A modular control and state estimation library for v5rc.
+//!
+//! Hardware is reached through the traits in [`hal`]. The `vexide` feature (on by
+//! default) implements them for vexide devices and runs control loops on vexide's
+//! runtime; without it the crate builds for the host with mock devices.
+
+#![no_std]
+// `clippy --all-targets` still builds the library as a unit test, which links std
+// and its inherent float methods shadow `float::Float`
+#![cfg_attr(test, allow(unused_imports))]
+// the runtimes are single threaded, so futures from trait methods never need `Send`
+#![allow(async_fn_in_trait)]
+
+extern crate alloc;
+
+pub mod arm;
+pub mod float;
+pub mod hal;
+pub mod localisation;
+pub mod matrix;
+pub mod pid;
+pub mod piston;
+pub mod rt;
+#[cfg(not(feature = "vexide"))]
+pub mod sim;
+pub mod tank_chassis;
+pub mod units;
@@ -0,0 +1,298 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    float::Float,
+    hal::{DistanceSensor, Imu},
+    localisation::{
+        measurement::{state_pose, DistanceMeasurement, ImuHeadingMeasurement, H, X, Y},
+        wrap_angle, AngleSystem, Localiser, Pose,
+    },
+    matrix::{Matrix, Vector},
+};
+
+/// Number of states, the pose indexed by [`X`], [`Y`] and [`H`]
+pub const STATES: usize = 3;
+
+pub type State = Vector<STATES>;
+pub type Covariance = Matrix<STATES, STATES>;
+/// Derivative of one measurement with respect to each state
+pub type Jacobian = Matrix<1, STATES>;
+
+/// Motion reported by odometry since the last update, in the frame of the previous
+/// pose
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Motion {
+    pub forward: f64,
+    pub right: f64,
+    /// radians counterclockwise
+    pub turn: f64,
+}
+
+/// How the pose changes with odometry's motion.
+pub trait ProcessModel {
+    fn predict(&self, state: &State, motion: &Motion) -> State;
+    /// derivative of [`ProcessModel::predict`] with respect to the state
+    fn jacobian(&self, state: &State, motion: &Motion) -> Covariance;
+    /// covariance of the error added by `motion`, in the field frame
+    fn noise(&self, state: &State, motion: &Motion) -> Covariance;
+}
+
+/// Applies odometry's motion directly, with noise proportional to it.
+pub struct OdometryModel {
+    dist_noise: f64,
+    turn_noise: f64,
+}
+
+impl OdometryModel {
+    /// Standard deviation of motion noise, as a fraction of the distance travelled and
+    /// of the angle turned.
+    pub fn new(dist_noise: f64, turn_noise: f64) -> Self {
+        OdometryModel {
+            dist_noise,
+            turn_noise,
+        }
+    }
+}
+
+impl Default for OdometryModel {
+    fn default() -> Self {
+        OdometryModel::new(0.05, 0.05)
+    }
+}
+
+impl ProcessModel for OdometryModel {
+    fn predict(&self, state: &State, motion: &Motion) -> State {
+        let (sin, cos) = state[H].sin_cos();
+        State::from_array([
+            state[X] + motion.forward * cos + motion.right * sin,
+            state[Y] + motion.forward * sin - motion.right * cos,
+            wrap_angle(state[H] + motion.turn),
+        ])
+    }
+
+    fn jacobian(&self, state: &State, motion: &Motion) -> Covariance {
+        let (sin, cos) = state[H].sin_cos();
+        Covariance::new([
+            [1.0, 0.0, -motion.forward * sin + motion.right * cos],
+            [0.0, 1.0, motion.forward * cos + motion.right * sin],
+            [0.0, 0.0, 1.0],
+        ])
+    }
+
+    fn noise(&self, state: &State, motion: &Motion) -> Covariance {
+        let dist = self.dist_noise * motion.forward.hypot(motion.right);
+        let turn = self.turn_noise * motion.turn.abs();
+        // from the robot frame to the field frame
+        let (sin, cos) = state[H].sin_cos();
+        let rotation = Covariance::new([[cos, sin, 0.0], [sin, -cos, 0.0], [0.0, 0.0, 1.0]]);
+        rotation
+            * Covariance::from_diagonal([dist * dist, dist * dist, turn * turn])
+            * rotation.transpose()
+    }
+}
+
+/// A sensor fused by [`EkfLocaliser`], giving one scalar reading per update.
+pub trait EkfMeasurement {
+    /// reads the sensor, `None` if it has nothing to fuse this update
+    fn read(&mut self) -> Option<f64>;
+    /// Reading expected in `state`, `None` if it can't be predicted, e.g. a distance
+    /// sensor facing no wall.
+    fn expected(&self, state: &State) -> Option<f64>;
+    /// derivative of [`EkfMeasurement::expected`] with respect to the state
+    fn jacobian(&self, state: &State) -> Option<Jacobian>;
+    /// variance of `reading`
+    fn variance(&self, reading: f64) -> f64;
+    /// angles have their residuals wrapped to (-π, π]
+    fn is_angle(&self) -> bool {
+        false
+    }
+    /// called when the localiser's pose is reset
+    fn set_pose(&mut self, _pose: Pose) {}
+}
+
+impl<I: Imu> EkfMeasurement for ImuHeadingMeasurement<I> {
+    fn read(&mut self) -> Option<f64> {
+        ImuHeadingMeasurement::read(self)
+    }
+
+    fn expected(&self, state: &State) -> Option<f64> {
+        Some(state[H])
+    }
+
+    fn jacobian(&self, _state: &State) -> Option<Jacobian> {
+        Some(Jacobian::new([[0.0, 0.0, 1.0]]))
+    }
+
+    fn variance(&self, _reading: f64) -> f64 {
+        ImuHeadingMeasurement::variance(self)
+    }
+
+    fn is_angle(&self) -> bool {
+        true
+    }
+
+    fn set_pose(&mut self, pose: Pose) {
+        ImuHeadingMeasurement::set_pose(self, pose);
+    }
+}
+
+impl<S: DistanceSensor> EkfMeasurement for DistanceMeasurement<S> {
+    fn read(&mut self) -> Option<f64> {
+        DistanceMeasurement::read(self)
+    }
+
+    fn expected(&self, state: &State) -> Option<f64> {
+        self.distance(state_pose(state))
+    }
+
+    fn jacobian(&self, state: &State) -> Option<Jacobian> {
+        self.gradient(state_pose(state))
+            .map(|gradient| Jacobian::new([gradient]))
+    }
+
+    fn variance(&self, reading: f64) -> f64 {
+        DistanceMeasurement::variance(self, reading)
+    }
+}
+
+/// Extended Kalman filter correcting odometry with any number of
+/// [`EkfMeasurement`]s.
+///
+/// The pose is moved by the change reported by `odometry` through the process model,
+/// then each reading is fused in turn using the measurement's Jacobian. A reading
+/// further than the gate from what the filter expects, in standard deviations, is
+/// treated as an outlier and ignored.
+pub struct EkfLocaliser<O: Localiser, P: ProcessModel> {
+    odometry: O,
+    process: P,
+    measurements: Vec<Box<dyn EkfMeasurement>>,
+    prev_odometry: Pose,
+    state: State,
+    covariance: Covariance,
+    spread: (f64, f64),
+    gate: f64,
+}
+
+impl<O: Localiser, P: ProcessModel> EkfLocaliser<O, P> {
+    pub fn new(odometry: O, process: P, measurements: Vec<Box<dyn EkfMeasurement>>) -> Self {
+        let pose = odometry.pose();
+        let mut localiser = EkfLocaliser {
+            odometry,
+            process,
+            measurements,
+            prev_odometry: pose,
+            state: State::zeros(),
+            covariance: Covariance::zeros(),
+            spread: (20.0, 2.0_f64.to_radians()),
+            gate: 4.0,
+        };
+        localiser.set_pose(pose);
+        localiser
+    }
+
+    /// Standard deviation of position and heading (radians) after
+    /// [`Localiser::set_pose`].
+    pub fn set_spread(&mut self, pos: f64, heading: f64) {
+        self.spread = (pos, heading);
+    }
+
+    /// readings further than `gate` standard deviations from expected are ignored
+    pub fn set_gate(&mut self, gate: f64) {
+        self.gate = gate;
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn covariance(&self) -> &Covariance {
+        &self.covariance
+    }
+
+    pub fn odometry(&self) -> &O {
+        &self.odometry
+    }
+
+    fn predict(&mut self, motion: &Motion) {
+        let jacobian = self.process.jacobian(&self.state, motion);
+        let noise = self.process.noise(&self.state, motion);
+        self.state = self.process.predict(&self.state, motion);
+        self.covariance = (jacobian * self.covariance * jacobian.transpose() + noise).symmetrise();
+    }
+
+    fn correct(&mut self, index: usize, reading: f64) {
+        let measurement = &self.measurements[index];
+        let (Some(expected), Some(jacobian)) = (
+            measurement.expected(&self.state),
+            measurement.jacobian(&self.state),
+        ) else {
+            return;
+        };
+
+        let noise = measurement.variance(reading);
+        let innovation = if measurement.is_angle() {
+            wrap_angle(reading - expected)
+        } else {
+            reading - expected
+        };
+        let variance = (jacobian * self.covariance * jacobian.transpose())[(0, 0)] + noise;
+        if innovation * innovation > self.gate * self.gate * variance {
+            return;
+        }
+
+        let gain = self.covariance * jacobian.transpose() * (1.0 / variance);
+        self.state += gain * innovation;
+        self.state[H] = wrap_angle(self.state[H]);
+        // joseph form, which stays symmetric and positive definite
+        let keep = Covariance::identity() - gain * jacobian;
+        self.covariance = (keep * self.covariance * keep.transpose()
+            + gain * gain.transpose() * noise)
+            .symmetrise();
+    }
+}
+
+impl<O: Localiser, P: ProcessModel> Localiser for EkfLocaliser<O, P> {
+    fn pose(&self) -> Pose {
+        state_pose(&self.state)
+    }
+
+    /// Resets the pose and its uncertainty.
+    fn set_pose(&mut self, pose: Pose) {
+        self.odometry.set_pose(pose);
+        self.prev_odometry = pose;
+        self.state = State::from_array([
+            pose.x.as_mm(),
+            pose.y.as_mm(),
+            wrap_angle(pose.h.as_rad(AngleSystem::Cartesian)),
+        ]);
+
+        let (pos, heading) = self.spread;
+        self.covariance = Covariance::from_diagonal([pos * pos, pos * pos, heading * heading]);
+
+        for measurement in self.measurements.iter_mut() {
+            measurement.set_pose(pose);
+        }
+    }
+
+    async fn update(&mut self) {
+        self.odometry.update().await;
+        let odometry = self.odometry.pose();
+
+        // motion since the last update, in the frame of the previous pose
+        let relative = odometry.relative_to(self.prev_odometry);
+        let motion = Motion {
+            forward: relative.x.as_mm(),
+            right: -relative.y.as_mm(),
+            turn: wrap_angle(relative.h.as_rad(AngleSystem::Cartesian)),
+        };
+        self.prev_odometry = odometry;
+
+        self.predict(&motion);
+        let readings: Vec<Option<f64>> = self.measurements.iter_mut().map(|m| m.read()).collect();
+        for (index, reading) in readings.into_iter().enumerate() {
+            if let Some(reading) = reading {
+                self.correct(index, reading);
+            }
+        }
+    }
+}
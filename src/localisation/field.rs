@@ -0,0 +1,112 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    float::Float,
+    localisation::{AngleSystem, Pose},
+    units::Length,
+};
+
+/// Side length of a standard field, in mm
+pub const FIELD_SIZE: f64 = 3657.6;
+
+/// Straight wall between two points on the field
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Wall {
+    pub start: (f64, f64),
+    pub end: (f64, f64),
+}
+
+impl Wall {
+    pub fn new(start: (f64, f64), end: (f64, f64)) -> Self {
+        Wall { start, end }
+    }
+
+    /// Distance along a ray to this wall, if the ray hits it.
+    ///
+    /// `angle` is in cartesian radians.
+    pub fn raycast(&self, origin: (f64, f64), angle: f64) -> Option<f64> {
+        let (sin, cos) = angle.sin_cos();
+        let wall_x = self.end.0 - self.start.0;
+        let wall_y = self.end.1 - self.start.1;
+        let denom = cos * wall_y - sin * wall_x;
+        if denom.abs() < 1e-12 {
+            // parallel
+            return None;
+        }
+        let to_x = self.start.0 - origin.0;
+        let to_y = self.start.1 - origin.1;
+        let dist = (to_x * wall_y - to_y * wall_x) / denom;
+        let along = (to_x * sin - to_y * cos) / denom;
+        (dist >= 0.0 && (0.0..=1.0).contains(&along)).then_some(dist)
+    }
+}
+
+/// Known walls for distance sensors to measure against
+#[derive(Clone, Debug)]
+pub struct Field {
+    walls: Vec<Wall>,
+}
+
+impl Field {
+    pub fn new(walls: Vec<Wall>) -> Self {
+        Field { walls }
+    }
+
+    /// Perimeter of a standard field, with the origin in the corner at `min`
+    pub fn perimeter(min: (f64, f64)) -> Self {
+        Field::rectangle(min, (min.0 + FIELD_SIZE, min.1 + FIELD_SIZE))
+    }
+
+    pub fn rectangle(min: (f64, f64), max: (f64, f64)) -> Self {
+        Field::new(vec![
+            Wall::new(min, (max.0, min.1)),
+            Wall::new((max.0, min.1), max),
+            Wall::new(max, (min.0, max.1)),
+            Wall::new((min.0, max.1), min),
+        ])
+    }
+
+    pub fn walls(&self) -> &[Wall] {
+        &self.walls
+    }
+
+    /// Nearest wall along a ray and the distance to it.
+    pub fn raycast(&self, origin: (f64, f64), angle: f64) -> Option<(f64, &Wall)> {
+        self.walls
+            .iter()
+            .filter_map(|wall| wall.raycast(origin, angle).map(|dist| (dist, wall)))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+/// Where a distance sensor sits on the robot.
+///
+/// Offsets are in the robot frame, where forward is +y and right is +x. The angle is
+/// in radians counterclockwise from forward.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SensorMount {
+    pub x: f64,
+    pub y: f64,
+    pub angle: f64,
+}
+
+impl SensorMount {
+    pub fn new(x: f64, y: f64, angle: f64) -> Self {
+        SensorMount { x, y, angle }
+    }
+
+    /// Origin and cartesian angle of the sensor's ray with the robot at `pose`.
+    pub fn ray(&self, pose: Pose) -> ((f64, f64), f64) {
+        let (x, y) = pose.transform_point((Length::from_mm(self.y), Length::from_mm(-self.x)));
+        (
+            (x.as_mm(), y.as_mm()),
+            pose.h.as_rad(AngleSystem::Cartesian) + self.angle,
+        )
+    }
+
+    /// Reading expected with the robot at `pose`, if the sensor faces a wall.
+    pub fn expected(&self, pose: Pose, field: &Field) -> Option<f64> {
+        let (origin, angle) = self.ray(pose);
+        field.raycast(origin, angle).map(|(dist, _)| dist)
+    }
+}
@@ -0,0 +1,140 @@
+//! Absolute heading for [`TrackingWheelLocaliser`](super::TrackingWheelLocaliser).
+
+use core::time::Duration;
+
+use crate::{
+    hal::Imu,
+    localisation::TrackingAxis,
+    rt::{sleep, Instant},
+    units::Angle,
+};
+
+// the v5 inertial sensor calibrates in about 2 s
+const CALIBRATION_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub trait HeadingSource {
+    /// cartesian, continuing past full turns
+    async fn heading(&mut self) -> Angle;
+    /// makes the current heading read as `heading`
+    fn set_heading(&mut self, heading: Angle);
+}
+
+// offsets raw readings so the heading can be reset between reads
+#[derive(Default)]
+struct Offset {
+    offset: Angle,
+    last_raw: Option<Angle>,
+    pending: Option<Angle>,
+}
+
+impl Offset {
+    fn apply(&mut self, raw: Angle) -> Angle {
+        self.last_raw = Some(raw);
+        if let Some(heading) = self.pending.take() {
+            self.offset = heading - raw;
+        }
+        self.offset + raw
+    }
+
+    // the heading without a new reading
+    fn held(&self) -> Angle {
+        match self.last_raw {
+            Some(raw) => self.offset + raw,
+            None => self.pending.unwrap_or(self.offset),
+        }
+    }
+
+    fn set(&mut self, heading: Angle) {
+        match self.last_raw {
+            Some(raw) => self.offset = heading - raw,
+            // nothing read yet, wait for the first reading
+            None => self.pending = Some(heading),
+        }
+    }
+}
+
+/// Heading from an inertial sensor.
+///
+/// The first reading waits for the sensor to finish calibrating, for at most 3 s.
+/// While it has no reading, whether still calibrating or disconnected, the last
+/// heading is held.
+pub struct ImuHeading<I: Imu> {
+    sensor: I,
+    scale: f64,
+    offset: Offset,
+    waited: bool,
+}
+
+impl<I: Imu> ImuHeading<I> {
+    pub fn new(sensor: I) -> Self {
+        ImuHeading {
+            sensor,
+            scale: 1.0,
+            offset: Offset::default(),
+            waited: false,
+        }
+    }
+
+    /// Corrects the sensor's drift by scaling its rotation, e.g. 3600 / 3590 if ten
+    /// full turns read 3590 degrees.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+
+    /// Waits for the sensor to finish calibrating, giving up after 3 s.
+    pub async fn wait_calibrated(&self) {
+        let start = Instant::now();
+        while self.sensor.is_calibrating() && start.elapsed() < CALIBRATION_TIMEOUT {
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+impl<I: Imu> HeadingSource for ImuHeading<I> {
+    async fn heading(&mut self) -> Angle {
+        if !self.waited {
+            self.wait_calibrated().await;
+            self.waited = true;
+        }
+        match self.sensor.rotation() {
+            // the sensor turns clockwise
+            Some(rotation) => self.offset.apply(-self.scale * Angle::from_deg(rotation)),
+            None => self.offset.held(),
+        }
+    }
+
+    fn set_heading(&mut self, heading: Angle) {
+        self.offset.set(heading);
+    }
+}
+
+/// Heading from the difference in travel of two parallel trackers.
+pub struct TrackerHeading<TL: TrackingAxis, TR: TrackingAxis> {
+    left: TL,
+    right: TR,
+    offset: Offset,
+}
+
+impl<TL: TrackingAxis, TR: TrackingAxis> TrackerHeading<TL, TR> {
+    /// `left` must have a smaller x offset than `right`
+    pub fn new(left: TL, right: TR) -> Self {
+        TrackerHeading {
+            left,
+            right,
+            offset: Offset::default(),
+        }
+    }
+}
+
+impl<TL: TrackingAxis, TR: TrackingAxis> HeadingSource for TrackerHeading<TL, TR> {
+    async fn heading(&mut self) -> Angle {
+        // turning left moves the tracker with the larger x offset further forward
+        let diff = self.right.dist().await - self.left.dist().await;
+        let raw = diff / (self.right.pos() - self.left.pos());
+        self.offset.apply(Angle::from_rad(raw))
+    }
+
+    fn set_heading(&mut self, heading: Angle) {
+        self.offset.set(heading);
+    }
+}
@@ -0,0 +1,160 @@
+//! Sensor models shared by the [`ekf`](super::ekf) and [`ukf`](super::ukf) filters.
+//!
+//! Both filters start their state with the pose, indexed by [`X`], [`Y`] and [`H`],
+//! which is all these sensors depend on.
+
+use crate::{
+    float::Float,
+    hal::{DistanceSensor, Imu},
+    localisation::{
+        field::{Field, SensorMount},
+        wrap_angle, AngleSystem, Heading, Pose,
+    },
+    matrix::Vector,
+    units::Length,
+};
+
+/// mm
+pub const X: usize = 0;
+/// mm
+pub const Y: usize = 1;
+/// cartesian radians
+pub const H: usize = 2;
+
+// readings further than this are unreliable
+const MAX_RANGE: f64 = 2000.0;
+
+/// The pose at the start of a filter's state
+pub fn state_pose<const N: usize>(state: &Vector<N>) -> Pose {
+    Pose::new(
+        Length::from_mm(state[X]),
+        Length::from_mm(state[Y]),
+        Heading::new(state[H]),
+    )
+}
+
+/// Absolute heading from an inertial sensor, aligned to the pose on
+/// [`Localiser::set_pose`](crate::localisation::Localiser::set_pose).
+pub struct ImuHeadingMeasurement<I: Imu> {
+    sensor: I,
+    // cartesian radians at zero rotation
+    offset: f64,
+    noise: f64,
+}
+
+impl<I: Imu> ImuHeadingMeasurement<I> {
+    pub fn new(sensor: I) -> Self {
+        ImuHeadingMeasurement {
+            sensor,
+            offset: 0.0,
+            noise: 0.5_f64.to_radians(),
+        }
+    }
+
+    /// standard deviation of the heading, radians
+    pub fn set_noise(&mut self, noise: f64) {
+        self.noise = noise;
+    }
+
+    /// heading in cartesian radians, `None` if the sensor isn't reading
+    pub fn read(&mut self) -> Option<f64> {
+        // the imu turns clockwise
+        let rotation = self.sensor.rotation()?;
+        Some(wrap_angle(self.offset - rotation.to_radians()))
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.noise * self.noise
+    }
+
+    /// aligns the heading read to `pose`
+    pub fn set_pose(&mut self, pose: Pose) {
+        if let Some(rotation) = self.sensor.rotation() {
+            self.offset = pose.h.as_rad(AngleSystem::Cartesian) + rotation.to_radians();
+        }
+    }
+}
+
+/// Distance from a sensor to the field wall it faces.
+pub struct DistanceMeasurement<S: DistanceSensor> {
+    sensor: S,
+    mount: SensorMount,
+    field: Field,
+    noise: (f64, f64),
+    min_confidence: f64,
+}
+
+impl<S: DistanceSensor> DistanceMeasurement<S> {
+    pub fn new(sensor: S, mount: SensorMount, field: Field) -> Self {
+        DistanceMeasurement {
+            sensor,
+            mount,
+            field,
+            noise: (15.0, 0.05),
+            min_confidence: 0.5,
+        }
+    }
+
+    /// Standard deviation of a reading, the larger of `min` and `fraction` of the
+    /// reading.
+    pub fn set_noise(&mut self, min: f64, fraction: f64) {
+        self.noise = (min, fraction);
+    }
+
+    /// readings with lower confidence are ignored
+    pub fn set_min_confidence(&mut self, min_confidence: f64) {
+        self.min_confidence = min_confidence;
+    }
+
+    pub fn mount(&self) -> &SensorMount {
+        &self.mount
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// distance in mm, `None` if there is no confident reading in range
+    pub fn read(&mut self) -> Option<f64> {
+        self.sensor
+            .object()
+            .filter(|r| r.confidence >= self.min_confidence && r.distance < MAX_RANGE)
+            .map(|r| r.distance)
+    }
+
+    pub fn variance(&self, reading: f64) -> f64 {
+        let sd = self.noise.0.max(self.noise.1 * reading);
+        sd * sd
+    }
+
+    /// Distance expected at `pose`, `None` if the sensor faces no wall.
+    pub fn distance(&self, pose: Pose) -> Option<f64> {
+        self.mount.expected(pose, &self.field)
+    }
+
+    /// Derivative of [`DistanceMeasurement::distance`] with respect to x, y and
+    /// heading.
+    pub fn gradient(&self, pose: Pose) -> Option<[f64; 3]> {
+        let mount = &self.mount;
+        let (origin, angle) = mount.ray(pose);
+        let (dist, wall) = self.field.raycast(origin, angle)?;
+
+        // the distance is n·(start - origin) / n·u, for the wall normal n and ray u
+        let normal = (wall.start.1 - wall.end.1, wall.end.0 - wall.start.0);
+        let (sin, cos) = angle.sin_cos();
+        let facing = normal.0 * cos + normal.1 * sin;
+        let (h_sin, h_cos) = pose.h.as_rad(AngleSystem::Cartesian).sin_cos();
+        // how the sensor's origin and direction move as the robot turns
+        let origin_turn = (
+            mount.x * h_cos - mount.y * h_sin,
+            mount.x * h_sin + mount.y * h_cos,
+        );
+        let ray_turn = normal.0 * -sin + normal.1 * cos;
+        Some([
+            -normal.0 / facing,
+            -normal.1 / facing,
+            -(normal.0 * origin_turn.0 + normal.1 * origin_turn.1) / facing
+                - dist * ray_turn / facing,
+        ])
+    }
+}
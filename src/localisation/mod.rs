@@ -1,21 +1,45 @@
-use alloc::sync::Arc;
-use core::f64::*;
+pub mod ekf;
+pub mod field;
+pub mod heading;
+pub mod measurement;
+pub mod particle;
+pub mod shared;
+pub mod ukf;
+pub mod wall_reset;
 
-use vexide::{
-    core::sync::Mutex,
-    prelude::{Position, RotationSensor},
+use alloc::{rc::Rc, sync::Arc};
+use core::{
+    f64::*,
+    ops::{Add, Neg, Sub},
 };
 
-use crate::tank_chassis::TankChassis;
+use crate::{
+    float::Float,
+    hal::{MotorGroup, RotaryEncoder},
+    localisation::heading::HeadingSource,
+    rt::Mutex,
+    tank_chassis::TankChassis,
+    units::{Angle, Length},
+};
 
 pub enum AngleSystem {
     Cartesian,
     Bearing,
 }
 
+/// Wraps an angle in radians to (-pi, pi].
+pub fn wrap_angle(rad: f64) -> f64 {
+    let wrapped = (rad + consts::PI).rem_euclid(consts::TAU) - consts::PI;
+    if wrapped == -consts::PI {
+        consts::PI
+    } else {
+        wrapped
+    }
+}
+
 // * internally stored as radians from the positive x axis
-// ? is this even needed
-#[derive(Copy, Clone)]
+// * arithmetic doesn't wrap, use `normalised` or `diff` where it matters
+#[derive(Copy, Clone, Debug)]
 pub struct Heading {
     rad: f64,
 }
@@ -37,6 +61,10 @@ impl Heading {
         heading
     }
 
+    pub fn from_angle(angle: Angle, system: AngleSystem) -> Self {
+        Heading::from_rad(angle.as_rad(), system)
+    }
+
     pub fn set_deg(&mut self, mut deg: f64, system: AngleSystem) {
         if matches!(system, AngleSystem::Bearing) {
             deg = 90.0 - deg;
@@ -66,18 +94,181 @@ impl Heading {
             self.rad
         }
     }
+
+    pub fn as_angle(&self, system: AngleSystem) -> Angle {
+        Angle::from_rad(self.as_rad(system))
+    }
+
+    /// cartesian radians in (-pi, pi]
+    pub fn normalised(&self) -> Self {
+        Heading::new(wrap_angle(self.rad))
+    }
+
+    /// cartesian radians in [0, 2pi)
+    pub fn normalised_positive(&self) -> Self {
+        Heading::new(self.rad.rem_euclid(consts::TAU))
+    }
+
+    /// Shortest signed angle from `other` to `self`, in radians counterclockwise.
+    pub fn diff(&self, other: Heading) -> f64 {
+        wrap_angle(self.rad - other.rad)
+    }
+
+    /// `t` of the way from `self` to `other` through the smaller angle
+    pub fn lerp(&self, other: Heading, t: f64) -> Self {
+        Heading::new(self.rad + other.diff(*self) * t)
+    }
+
+    /// Circular mean, `None` if there are no headings or they cancel out.
+    pub fn mean<I: IntoIterator<Item = Heading>>(headings: I) -> Option<Self> {
+        Heading::weighted_mean(headings.into_iter().map(|heading| (heading, 1.0)))
+    }
+
+    /// Circular mean of (heading, weight) pairs, `None` if there are no headings or
+    /// they cancel out.
+    pub fn weighted_mean<I: IntoIterator<Item = (Heading, f64)>>(headings: I) -> Option<Self> {
+        let (mut sin, mut cos) = (0.0, 0.0);
+        for (heading, weight) in headings {
+            let (h_sin, h_cos) = heading.rad.sin_cos();
+            sin += weight * h_sin;
+            cos += weight * h_cos;
+        }
+        (sin.hypot(cos) > 1e-12).then(|| Heading::new(sin.atan2(cos)))
+    }
+
+    /// equal to within `tolerance` radians, regardless of whole turns
+    pub fn approx_eq(&self, other: Heading, tolerance: f64) -> bool {
+        self.diff(other).abs() <= tolerance
+    }
 }
-#[derive(Copy, Clone)]
+
+impl Add for Heading {
+    type Output = Heading;
+
+    fn add(self, rhs: Heading) -> Heading {
+        Heading::new(self.rad + rhs.rad)
+    }
+}
+
+impl Sub for Heading {
+    type Output = Heading;
+
+    fn sub(self, rhs: Heading) -> Heading {
+        Heading::new(self.rad - rhs.rad)
+    }
+}
+
+impl Neg for Heading {
+    type Output = Heading;
+
+    fn neg(self) -> Heading {
+        Heading::new(-self.rad)
+    }
+}
+
+/// Position and heading on the field.
+///
+/// Relative poses, points and twists used with the operations below are in a pose's
+/// own frame, x along its heading and y to its left. The robot frame used by trackers
+/// and sensor mounts, forward +y and right +x, is this frame turned a quarter turn
+/// clockwise, so robot (x, y) is (y, -x) here.
+#[derive(Copy, Clone, Debug)]
 pub struct Pose {
-    pub x: f64,
-    pub y: f64,
+    pub x: Length,
+    pub y: Length,
     pub h: Heading,
 }
 
+/// Constant curvature motion in a pose's own frame, see [`Pose::exp`]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PoseTwist {
+    /// along the heading
+    pub dx: Length,
+    /// to the left
+    pub dy: Length,
+    pub d_theta: Angle,
+}
+
+impl PoseTwist {
+    pub fn new(dx: Length, dy: Length, d_theta: Angle) -> Self {
+        PoseTwist { dx, dy, d_theta }
+    }
+}
+
 impl Pose {
-    pub fn new(x: f64, y: f64, h: Heading) -> Self {
+    pub fn new(x: Length, y: Length, h: Heading) -> Self {
         Pose { x, y, h }
     }
+
+    pub fn identity() -> Self {
+        Pose::new(Length::ZERO, Length::ZERO, Heading::new(0.0))
+    }
+
+    /// `other`, given in this pose's frame, in the field frame
+    pub fn compose(&self, other: Pose) -> Pose {
+        let (x, y) = self.transform_point((other.x, other.y));
+        Pose::new(x, y, self.h + other.h)
+    }
+
+    /// the field origin in this pose's frame, so `p.compose(p.inverse())` is the identity
+    pub fn inverse(&self) -> Pose {
+        let (x, y) = self.inverse_transform_point((Length::ZERO, Length::ZERO));
+        Pose::new(x, y, -self.h)
+    }
+
+    /// this pose in the frame of `base`
+    pub fn relative_to(&self, base: Pose) -> Pose {
+        base.inverse().compose(*self)
+    }
+
+    /// A point in this pose's frame, in the field frame.
+    pub fn transform_point(&self, point: (Length, Length)) -> (Length, Length) {
+        let (sin, cos) = self.h.as_rad(AngleSystem::Cartesian).sin_cos();
+        (
+            self.x + point.0 * cos - point.1 * sin,
+            self.y + point.0 * sin + point.1 * cos,
+        )
+    }
+
+    /// A point in the field frame, in this pose's frame.
+    pub fn inverse_transform_point(&self, point: (Length, Length)) -> (Length, Length) {
+        let (sin, cos) = self.h.as_rad(AngleSystem::Cartesian).sin_cos();
+        let (dx, dy) = (point.0 - self.x, point.1 - self.y);
+        (dx * cos + dy * sin, -dx * sin + dy * cos)
+    }
+
+    /// Relative pose reached by following `twist` along a constant curvature arc.
+    pub fn exp(twist: PoseTwist) -> Pose {
+        let theta = twist.d_theta.as_rad();
+        // sin(theta) / theta and (1 - cos(theta)) / theta, by series near zero
+        let (s, c) = if theta.abs() < 1e-9 {
+            (1.0 - theta * theta / 6.0, theta / 2.0)
+        } else {
+            (theta.sin() / theta, (1.0 - theta.cos()) / theta)
+        };
+        Pose::new(
+            twist.dx * s - twist.dy * c,
+            twist.dx * c + twist.dy * s,
+            Heading::new(theta),
+        )
+    }
+
+    /// The constant curvature twist reaching this pose from the identity, turning
+    /// less than half a turn either way.
+    pub fn log(&self) -> PoseTwist {
+        let theta = wrap_angle(self.h.as_rad(AngleSystem::Cartesian));
+        let half = theta / 2.0;
+        let a = if theta.abs() < 1e-9 {
+            1.0 - theta * theta / 12.0
+        } else {
+            half * theta.sin() / (1.0 - theta.cos())
+        };
+        PoseTwist::new(
+            self.x * a + self.y * half,
+            -self.x * half + self.y * a,
+            Angle::from_rad(theta),
+        )
+    }
 }
 
 impl core::fmt::Display for Pose {
@@ -85,8 +276,8 @@ impl core::fmt::Display for Pose {
         write!(
             f,
             "({}, {}, {})",
-            self.x,
-            self.y,
+            self.x.as_mm(),
+            self.y.as_mm(),
             self.h.as_rad(AngleSystem::Cartesian)
         )
     }
@@ -95,90 +286,195 @@ impl core::fmt::Display for Pose {
 pub trait Localiser {
     fn pose(&self) -> Pose;
     fn set_pose(&mut self, pose: Pose);
+    /// reads the sensors and updates the pose estimate
+    async fn update(&mut self);
 }
 
-// TODO: make pose thread-safe
-pub struct TrackingWheelLocaliser<TX: TrackingAxis, TY: TrackingAxis> {
+/// Odometry from one lateral tracker, one parallel tracker and a heading source.
+///
+/// Trackers are described in the robot frame, where forward is +y and right is +x.
+/// The parallel tracker measures forward travel and is offset along x; the lateral
+/// tracker measures travel to the right and is offset along y.
+pub struct TrackingWheelLocaliser<TX: TrackingAxis, TY: TrackingAxis, HS: HeadingSource> {
     x_axis: TX,
     y_axis: TY,
+    heading: HS,
+    prev: Option<(Length, Length, Angle)>,
     pose: Pose,
 }
 
-impl<TX: TrackingAxis, TY: TrackingAxis> TrackingWheelLocaliser<TX, TY> {
-    pub fn from_chassis_and_wheel(x_tracker: TX, y_tracker: TY, init_pose: Pose) -> Self {
+impl<TX: TrackingAxis, TY: TrackingAxis, HS: HeadingSource> TrackingWheelLocaliser<TX, TY, HS> {
+    /// `heading` is reset to the heading of `init_pose`.
+    ///
+    /// With only tracking wheels, `heading` can be a
+    /// [`TrackerHeading`](heading::TrackerHeading) of two parallel trackers at
+    /// different x offsets.
+    pub fn new(x_tracker: TX, y_tracker: TY, mut heading: HS, init_pose: Pose) -> Self {
+        heading.set_heading(Angle::from_rad(init_pose.h.as_rad(AngleSystem::Cartesian)));
         TrackingWheelLocaliser {
             x_axis: x_tracker,
             y_axis: y_tracker,
+            heading,
+            prev: None,
             pose: init_pose,
         }
     }
-
-    pub async fn update(&mut self) {
-        self.pose.x = self.x_axis.deg().await;
-        self.pose.y = self.y_axis.deg().await;
-        self.pose.h.set_deg(0.0, AngleSystem::Cartesian);
-    }
 }
 
-impl<TX: TrackingAxis, TY: TrackingAxis> Localiser for TrackingWheelLocaliser<TX, TY> {
+impl<TX: TrackingAxis, TY: TrackingAxis, HS: HeadingSource> Localiser
+    for TrackingWheelLocaliser<TX, TY, HS>
+{
     fn pose(&self) -> Pose {
         self.pose
     }
     fn set_pose(&mut self, pose: Pose) {
+        let heading = Angle::from_rad(pose.h.as_rad(AngleSystem::Cartesian));
+        self.heading.set_heading(heading);
+        if let Some(prev) = self.prev.as_mut() {
+            prev.2 = heading;
+        }
         self.pose = pose;
     }
+
+    async fn update(&mut self) {
+        let x_dist = self.x_axis.dist().await;
+        let y_dist = self.y_axis.dist().await;
+        let heading = self.heading.heading().await;
+
+        // first reading only sets the reference
+        let Some((prev_x, prev_y, prev_heading)) = self.prev.replace((x_dist, y_dist, heading))
+        else {
+            return;
+        };
+
+        let dx = x_dist - prev_x;
+        let dy = y_dist - prev_y;
+        let d_theta = heading - prev_heading;
+
+        // remove the travel caused by rotation to get the motion of the tracking centre
+        let local_y = dy - self.y_axis.pos() * d_theta.as_rad();
+        let local_x = dx + self.x_axis.pos() * d_theta.as_rad();
+
+        // the tracking centre moves along an arc
+        let twist = PoseTwist::new(local_y, -local_x, d_theta);
+        self.pose = self.pose.compose(Pose::exp(twist));
+        self.pose
+            .h
+            .set_rad(heading.as_rad(), AngleSystem::Cartesian);
+    }
 }
 
 pub trait TrackingAxis {
-    async fn deg(&self) -> f64;
-    fn pos(&self) -> f64;
+    /// how far the wheel has turned
+    async fn angle(&self) -> Angle;
+    /// offset of the tracker from the tracking centre, perpendicular to its travel
+    fn pos(&self) -> Length;
+    fn wheel_diameter(&self) -> Length;
+
+    async fn dist(&self) -> Length {
+        self.angle().await.arc_length(self.wheel_diameter())
+    }
+}
+
+// lets one tracker feed both the localiser and a heading source
+impl<T: TrackingAxis> TrackingAxis for Rc<T> {
+    async fn angle(&self) -> Angle {
+        (**self).angle().await
+    }
+
+    fn pos(&self) -> Length {
+        (**self).pos()
+    }
+
+    fn wheel_diameter(&self) -> Length {
+        (**self).wheel_diameter()
+    }
 }
 
-pub struct TrackerAxisWheel {
-    sensor: RotationSensor,
-    pos: f64,
+pub struct TrackerAxisWheel<E: RotaryEncoder> {
+    sensor: E,
+    pos: Length,
+    wheel_diameter: Length,
 }
 
-impl TrackerAxisWheel {
-    pub fn new(sensor: RotationSensor, pos: f64) -> Self {
-        TrackerAxisWheel { sensor, pos }
+impl<E: RotaryEncoder> TrackerAxisWheel<E> {
+    pub fn new(sensor: E, pos: Length, wheel_diameter: Length) -> Self {
+        TrackerAxisWheel {
+            sensor,
+            pos,
+            wheel_diameter,
+        }
     }
 }
 
-impl TrackingAxis for TrackerAxisWheel {
-    async fn deg(&self) -> f64 {
-        self.sensor
-            .position()
-            .unwrap_or(Position::from_degrees(0.0))
-            .as_degrees()
+impl<E: RotaryEncoder> TrackingAxis for TrackerAxisWheel<E> {
+    async fn angle(&self) -> Angle {
+        Angle::from_deg(self.sensor.position())
     }
 
-    fn pos(&self) -> f64 {
+    fn pos(&self) -> Length {
         self.pos
     }
+
+    fn wheel_diameter(&self) -> Length {
+        self.wheel_diameter
+    }
 }
 
-pub struct TrackerAxisDrive {
-    chassis: Arc<Mutex<TankChassis>>,
-    track_width: f64,
+/// Which way a tracking wheel rolls, as described for [`TrackingWheelLocaliser`]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TrackerDirection {
+    /// measures forward travel, offset along x
+    Parallel,
+    /// measures travel to the right, offset along y
+    Lateral,
 }
 
-impl TrackerAxisDrive {
-    pub fn new(chassis: Arc<Mutex<TankChassis>>, track_width: f64) -> Self {
+pub enum ChassisSide {
+    Left,
+    Right,
+}
+
+pub struct TrackerAxisDrive<M: MotorGroup> {
+    chassis: Arc<Mutex<TankChassis<M>>>,
+    side: ChassisSide,
+    track_width: Length,
+    wheel_diameter: Length,
+}
+
+impl<M: MotorGroup> TrackerAxisDrive<M> {
+    /// `wheel_diameter` should be scaled by any gearing between the motors and the wheels
+    pub fn new(
+        chassis: Arc<Mutex<TankChassis<M>>>,
+        side: ChassisSide,
+        track_width: Length,
+        wheel_diameter: Length,
+    ) -> Self {
         TrackerAxisDrive {
             chassis,
+            side,
             track_width,
+            wheel_diameter,
         }
     }
 }
 
-impl TrackingAxis for TrackerAxisDrive {
-    async fn deg(&self) -> f64 {
-        self.chassis.lock().await.right_deg()
+impl<M: MotorGroup> TrackingAxis for TrackerAxisDrive<M> {
+    async fn angle(&self) -> Angle {
+        match self.side {
+            ChassisSide::Left => self.chassis.lock().await.left_angle(),
+            ChassisSide::Right => self.chassis.lock().await.right_angle(),
+        }
+    }
+
+    fn pos(&self) -> Length {
+        match self.side {
+            ChassisSide::Left => -self.track_width / 2.0,
+            ChassisSide::Right => self.track_width / 2.0,
+        }
     }
 
-    // cartesian coordinate
-    fn pos(&self) -> f64 {
-        self.track_width / 2.0
+    fn wheel_diameter(&self) -> Length {
+        self.wheel_diameter
     }
 }
@@ -0,0 +1,307 @@
+use alloc::{vec, vec::Vec};
+use core::f64::consts;
+
+use crate::{
+    float::Float,
+    hal::DistanceSensor,
+    localisation::{
+        field::{Field, SensorMount},
+        wrap_angle, AngleSystem, Heading, Localiser, Pose,
+    },
+    units::Length,
+};
+
+// readings further than this are unreliable
+const MAX_RANGE: f64 = 2000.0;
+// chance a reading is of something other than a wall, e.g. another robot
+const OUTLIER_CHANCE: f64 = 0.05;
+// noise added to resampled particles so copies don't stay identical while stationary
+const ROUGHEN_POS: f64 = 1.0;
+const ROUGHEN_HEADING: f64 = 0.2 * consts::PI / 180.0;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Particle {
+    pub x: f64,
+    pub y: f64,
+    /// cartesian radians
+    pub h: f64,
+    pub weight: f64,
+}
+
+/// Monte Carlo localisation against the field walls.
+///
+/// Particles are moved by the change in pose reported by `odometry`, with noise, then
+/// weighted by how well each distance sensor's reading matches the wall it would be
+/// facing. They are resampled with low variance resampling once the weights become
+/// uneven.
+///
+/// Each update raycasts every sensor from every particle against every wall, so the
+/// particle count should be chosen to fit the loop period.
+pub struct ParticleFilterLocaliser<O: Localiser, S: DistanceSensor> {
+    odometry: O,
+    field: Field,
+    sensors: Vec<(S, SensorMount)>,
+    particles: Vec<Particle>,
+    rng: XorShift,
+    prev_odometry: Pose,
+    pose: Pose,
+
+    dist_noise: f64,
+    turn_noise: f64,
+    sensor_noise: (f64, f64),
+    min_confidence: f64,
+    spread: (f64, f64),
+}
+
+impl<O: Localiser, S: DistanceSensor> ParticleFilterLocaliser<O, S> {
+    pub fn new(odometry: O, field: Field, sensors: Vec<(S, SensorMount)>, count: usize) -> Self {
+        let pose = odometry.pose();
+        let mut localiser = ParticleFilterLocaliser {
+            odometry,
+            field,
+            sensors,
+            particles: vec![
+                Particle {
+                    x: 0.0,
+                    y: 0.0,
+                    h: 0.0,
+                    weight: 0.0,
+                };
+                count.max(1)
+            ],
+            rng: XorShift(0x3818_3818_3818_3818),
+            prev_odometry: pose,
+            pose,
+            dist_noise: 0.05,
+            turn_noise: 0.05,
+            sensor_noise: (15.0, 0.05),
+            min_confidence: 0.5,
+            spread: (20.0, 2.0_f64.to_radians()),
+        };
+        localiser.set_pose(pose);
+        localiser
+    }
+
+    /// Standard deviation of motion noise, as a fraction of the distance travelled and
+    /// of the angle turned.
+    pub fn set_motion_noise(&mut self, dist_noise: f64, turn_noise: f64) {
+        self.dist_noise = dist_noise;
+        self.turn_noise = turn_noise;
+    }
+
+    /// Standard deviation of a distance reading, the larger of `min` and `fraction`
+    /// of the reading.
+    pub fn set_sensor_noise(&mut self, min: f64, fraction: f64) {
+        self.sensor_noise = (min, fraction);
+    }
+
+    /// readings with lower confidence are ignored
+    pub fn set_min_confidence(&mut self, min_confidence: f64) {
+        self.min_confidence = min_confidence;
+    }
+
+    /// Standard deviation of position and heading (radians) when particles are
+    /// scattered by [`Localiser::set_pose`].
+    pub fn set_spread(&mut self, pos: f64, heading: f64) {
+        self.spread = (pos, heading);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        // xorshift can't leave zero
+        self.rng = XorShift(seed.max(1));
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn odometry(&self) -> &O {
+        &self.odometry
+    }
+
+    // moves every particle by the odometry's motion in its own frame
+    fn predict(&mut self, forward: f64, right: f64, turn: f64) {
+        let dist = forward.hypot(right);
+        for particle in self.particles.iter_mut() {
+            let forward = forward + self.rng.gaussian(self.dist_noise * dist);
+            let right = right + self.rng.gaussian(self.dist_noise * dist);
+            let turn = turn + self.rng.gaussian(self.turn_noise * turn.abs());
+
+            let (sin, cos) = particle.h.sin_cos();
+            particle.x += forward * cos + right * sin;
+            particle.y += forward * sin - right * cos;
+            particle.h += turn;
+        }
+    }
+
+    // reweights by the likelihood of the readings, returns false if there were none
+    fn correct(&mut self) -> bool {
+        let readings: Vec<(f64, SensorMount)> = self
+            .sensors
+            .iter()
+            .filter_map(|(sensor, mount)| {
+                sensor
+                    .object()
+                    .filter(|r| r.confidence >= self.min_confidence && r.distance < MAX_RANGE)
+                    .map(|r| (r.distance, *mount))
+            })
+            .collect();
+        if readings.is_empty() {
+            return false;
+        }
+
+        let mut total = 0.0;
+        for particle in self.particles.iter_mut() {
+            let pose = Pose::new(
+                Length::from_mm(particle.x),
+                Length::from_mm(particle.y),
+                Heading::new(particle.h),
+            );
+            for (distance, mount) in readings.iter() {
+                let likelihood = match mount.expected(pose, &self.field) {
+                    Some(expected) => {
+                        let sd = self.sensor_noise.0.max(self.sensor_noise.1 * expected);
+                        let z = (distance - expected) / sd;
+                        (-0.5 * z * z).exp() / (sd * (consts::TAU).sqrt())
+                    }
+                    None => 0.0,
+                };
+                particle.weight *= (1.0 - OUTLIER_CHANCE) * likelihood + OUTLIER_CHANCE / MAX_RANGE;
+            }
+            total += particle.weight;
+        }
+
+        let count = self.particles.len() as f64;
+        for particle in self.particles.iter_mut() {
+            particle.weight = if total > 0.0 {
+                particle.weight / total
+            } else {
+                1.0 / count
+            };
+        }
+        true
+    }
+
+    // low variance resampling, only once the effective sample size has halved
+    fn resample(&mut self) {
+        let count = self.particles.len();
+        let effective = 1.0
+            / self
+                .particles
+                .iter()
+                .map(|p| p.weight * p.weight)
+                .sum::<f64>();
+        if effective > count as f64 / 2.0 {
+            return;
+        }
+
+        let step = 1.0 / count as f64;
+        let mut target = self.rng.uniform() * step;
+        let mut cumulative = self.particles[0].weight;
+        let mut i = 0;
+        let mut resampled = Vec::with_capacity(count);
+        for _ in 0..count {
+            while target > cumulative && i < count - 1 {
+                i += 1;
+                cumulative += self.particles[i].weight;
+            }
+            let particle = self.particles[i];
+            resampled.push(Particle {
+                x: particle.x + self.rng.gaussian(ROUGHEN_POS),
+                y: particle.y + self.rng.gaussian(ROUGHEN_POS),
+                h: particle.h + self.rng.gaussian(ROUGHEN_HEADING),
+                weight: step,
+            });
+            target += step;
+        }
+        self.particles = resampled;
+    }
+
+    fn estimate(&self) -> Pose {
+        let (mut x, mut y) = (0.0, 0.0);
+        for particle in self.particles.iter() {
+            x += particle.weight * particle.x;
+            y += particle.weight * particle.y;
+        }
+        let heading = Heading::weighted_mean(
+            self.particles
+                .iter()
+                .map(|particle| (Heading::new(particle.h), particle.weight)),
+        );
+        Pose::new(
+            Length::from_mm(x),
+            Length::from_mm(y),
+            heading.unwrap_or(self.pose.h),
+        )
+    }
+}
+
+impl<O: Localiser, S: DistanceSensor> Localiser for ParticleFilterLocaliser<O, S> {
+    fn pose(&self) -> Pose {
+        self.pose
+    }
+
+    /// Scatters the particles around `pose`.
+    fn set_pose(&mut self, pose: Pose) {
+        self.odometry.set_pose(pose);
+        self.prev_odometry = pose;
+        self.pose = pose;
+
+        let weight = 1.0 / self.particles.len() as f64;
+        let heading = pose.h.as_rad(AngleSystem::Cartesian);
+        for particle in self.particles.iter_mut() {
+            *particle = Particle {
+                x: pose.x.as_mm() + self.rng.gaussian(self.spread.0),
+                y: pose.y.as_mm() + self.rng.gaussian(self.spread.0),
+                h: heading + self.rng.gaussian(self.spread.1),
+                weight,
+            };
+        }
+    }
+
+    async fn update(&mut self) {
+        self.odometry.update().await;
+        let odometry = self.odometry.pose();
+
+        // motion since the last update, in the frame of the previous pose
+        let motion = odometry.relative_to(self.prev_odometry);
+        self.prev_odometry = odometry;
+
+        self.predict(
+            motion.x.as_mm(),
+            -motion.y.as_mm(),
+            wrap_angle(motion.h.as_rad(AngleSystem::Cartesian)),
+        );
+        if self.correct() {
+            self.resample();
+        }
+        self.pose = self.estimate();
+    }
+}
+
+/// Small, fast generator for particle noise
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// normally distributed with mean 0, by the Box-Muller transform
+    fn gaussian(&mut self, sd: f64) -> f64 {
+        if sd == 0.0 {
+            return 0.0;
+        }
+        let u1 = 1.0 - self.uniform();
+        let u2 = self.uniform();
+        sd * (-2.0 * u1.ln()).sqrt() * (consts::TAU * u2).cos()
+    }
+}
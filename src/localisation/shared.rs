@@ -0,0 +1,165 @@
+use alloc::sync::Arc;
+use core::time::Duration;
+
+use crate::{
+    localisation::{Localiser, Pose},
+    rt::{sleep_until, Instant, Mutex},
+};
+
+struct State {
+    pose: Pose,
+    paused: bool,
+    // set by a holder, waiting to be applied to the localiser
+    reset: Option<Pose>,
+}
+
+/// Cheap, cloneable handle to the pose published by a [`LocalisationRunner`].
+///
+/// The lock is only held to copy a pose in or out, never across an await, so reads
+/// and writes wait on nothing longer than another copy. As a [`Localiser`], `update`
+/// fetches the latest published pose and sends a pending `set_pose`, so the handle
+/// can be passed straight to chassis commands while the runner keeps it current.
+#[derive(Clone)]
+pub struct SharedPose {
+    state: Arc<Mutex<State>>,
+    // as of the last `Localiser::update`
+    pose: Pose,
+    // from `Localiser::set_pose`, sent on the next update
+    reset: Option<Pose>,
+}
+
+impl SharedPose {
+    pub fn new(pose: Pose) -> Self {
+        SharedPose {
+            state: Arc::new(Mutex::new(State {
+                pose,
+                paused: false,
+                reset: None,
+            })),
+            pose,
+            reset: None,
+        }
+    }
+
+    async fn with<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+        f(&mut *self.state.lock().await)
+    }
+
+    /// latest published pose
+    pub async fn pose(&self) -> Pose {
+        self.with(|state| state.pose).await
+    }
+
+    /// Publishes `pose` immediately and resets the localiser to it on the runner's
+    /// next update.
+    pub async fn set_pose(&self, pose: Pose) {
+        self.with(|state| {
+            state.pose = pose;
+            state.reset = Some(pose);
+        })
+        .await;
+    }
+
+    /// Stops the runner from updating the localiser. The pose holds still until
+    /// [`SharedPose::resume`], then catches up with any motion in between.
+    pub async fn pause(&self) {
+        self.with(|state| state.paused = true).await;
+    }
+
+    pub async fn resume(&self) {
+        self.with(|state| state.paused = false).await;
+    }
+
+    pub async fn is_paused(&self) -> bool {
+        self.with(|state| state.paused).await
+    }
+}
+
+impl Localiser for SharedPose {
+    /// the pose fetched by the last update
+    fn pose(&self) -> Pose {
+        self.pose
+    }
+
+    /// published on the next update
+    fn set_pose(&mut self, pose: Pose) {
+        self.pose = pose;
+        self.reset = Some(pose);
+    }
+
+    /// the runner updates the localiser in the background
+    async fn update(&mut self) {
+        if let Some(pose) = self.reset.take() {
+            SharedPose::set_pose(self, pose).await;
+        }
+        self.pose = SharedPose::pose(self).await;
+    }
+}
+
+/// Owns a localiser and updates it at a fixed rate, publishing each pose to
+/// [`SharedPose`] handles.
+pub struct LocalisationRunner<L: Localiser> {
+    localiser: L,
+    shared: SharedPose,
+    period: Duration,
+}
+
+impl<L: Localiser> LocalisationRunner<L> {
+    /// Starts from the localiser's current pose, updating every `period`.
+    pub fn new(localiser: L, period: Duration) -> Self {
+        let shared = SharedPose::new(localiser.pose());
+        LocalisationRunner {
+            localiser,
+            shared,
+            period,
+        }
+    }
+
+    pub fn handle(&self) -> SharedPose {
+        self.shared.clone()
+    }
+
+    /// Applies a pending [`SharedPose::set_pose`], then updates the localiser and
+    /// publishes its pose unless paused.
+    pub async fn step(&mut self) {
+        let (reset, paused) = self
+            .shared
+            .with(|state| (state.reset.take(), state.paused))
+            .await;
+        if let Some(pose) = reset {
+            self.localiser.set_pose(pose);
+        }
+        if paused {
+            return;
+        }
+
+        self.localiser.update().await;
+        let pose = self.localiser.pose();
+        self.shared
+            .with(|state| {
+                // a reset during the update wins, it's applied next step
+                if state.reset.is_none() {
+                    state.pose = pose;
+                }
+            })
+            .await;
+    }
+
+    /// Steps every period, forever.
+    pub async fn run(mut self) {
+        loop {
+            let start = Instant::now();
+            self.step().await;
+            sleep_until(start + self.period).await;
+        }
+    }
+
+    /// Runs in a background task, which stops when the returned task is dropped.
+    #[cfg(feature = "vexide")]
+    pub fn spawn(self) -> vexide::async_runtime::task::Task<()>
+    where
+        L: 'static,
+    {
+        vexide::async_runtime::spawn(self.run())
+    }
+}
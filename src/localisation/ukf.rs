@@ -0,0 +1,437 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+
+use crate::{
+    float::Float,
+    hal::{DistanceSensor, Imu, MotorGroup, RotaryEncoder},
+    localisation::{
+        measurement::{state_pose, DistanceMeasurement, ImuHeadingMeasurement, H, X, Y},
+        wrap_angle, AngleSystem, ChassisSide, Heading, Localiser, Pose, TrackerDirection,
+    },
+    matrix::{Matrix, Vector},
+    rt::{Instant, Mutex},
+    tank_chassis::{kinematics::Twist, TankChassis},
+    units::{AngularVelocity, Length, LinearVelocity},
+};
+
+/// Number of states, indexed by [`X`], [`Y`], [`H`], [`V`] and [`W`]
+pub const STATES: usize = 5;
+/// forward velocity, mm/s
+pub const V: usize = 3;
+/// angular velocity, rad/s counterclockwise
+pub const W: usize = 4;
+
+pub type State = Vector<STATES>;
+pub type Covariance = Matrix<STATES, STATES>;
+
+/// A sensor fused by [`UkfLocaliser`], giving one scalar reading per update.
+pub trait UkfMeasurement {
+    /// Reads the sensor, `None` if it has nothing to fuse this update.
+    ///
+    /// Called once per update, including the first, so sensors measuring change can
+    /// keep their reference current.
+    fn read(&mut self) -> Option<f64>;
+    /// Reading expected in `state`, `dt` seconds after the last update. `None` if the
+    /// reading can't be predicted, e.g. a distance sensor facing no wall.
+    fn expected(&self, state: &State, dt: f64) -> Option<f64>;
+    /// variance of `reading`
+    fn variance(&self, reading: f64) -> f64;
+    /// angles have their residuals wrapped to (-π, π]
+    fn is_angle(&self) -> bool {
+        false
+    }
+    /// called when the localiser's pose is reset
+    fn set_pose(&mut self, _pose: Pose) {}
+}
+
+// travel of a tracker at `offset` over `dt`, assuming the wheels don't slip sideways
+fn tracker_travel(state: &State, dt: f64, direction: TrackerDirection, offset: f64) -> f64 {
+    match direction {
+        TrackerDirection::Parallel => (state[V] + state[W] * offset) * dt,
+        TrackerDirection::Lateral => -state[W] * offset * dt,
+    }
+}
+
+/// Distance travelled by a tracking wheel since the last update.
+pub struct TrackerMeasurement<E: RotaryEncoder> {
+    sensor: E,
+    direction: TrackerDirection,
+    offset: Length,
+    wheel_diameter: Length,
+    prev_dist: Option<f64>,
+    noise: f64,
+}
+
+impl<E: RotaryEncoder> TrackerMeasurement<E> {
+    /// `offset` is perpendicular to the tracker's travel, as in
+    /// [`TrackingAxis::pos`](crate::localisation::TrackingAxis::pos)
+    pub fn new(
+        sensor: E,
+        direction: TrackerDirection,
+        offset: Length,
+        wheel_diameter: Length,
+    ) -> Self {
+        TrackerMeasurement {
+            sensor,
+            direction,
+            offset,
+            wheel_diameter,
+            prev_dist: None,
+            noise: 0.5,
+        }
+    }
+
+    /// standard deviation of the travel each update, mm
+    pub fn set_noise(&mut self, noise: f64) {
+        self.noise = noise;
+    }
+}
+
+impl<E: RotaryEncoder> UkfMeasurement for TrackerMeasurement<E> {
+    fn read(&mut self) -> Option<f64> {
+        let dist = self.sensor.position().to_radians() * self.wheel_diameter.as_mm() / 2.0;
+        self.prev_dist.replace(dist).map(|prev| dist - prev)
+    }
+
+    fn expected(&self, state: &State, dt: f64) -> Option<f64> {
+        Some(tracker_travel(
+            state,
+            dt,
+            self.direction,
+            self.offset.as_mm(),
+        ))
+    }
+
+    fn variance(&self, _reading: f64) -> f64 {
+        self.noise * self.noise
+    }
+}
+
+/// Distance travelled by one side of the drive since the last update.
+///
+/// The chassis is only read if it isn't locked, so the reading is skipped rather
+/// than waiting on a controller.
+pub struct DriveSideMeasurement<M: MotorGroup> {
+    chassis: Arc<Mutex<TankChassis<M>>>,
+    side: ChassisSide,
+    track_width: Length,
+    wheel_diameter: Length,
+    prev_dist: Option<f64>,
+    noise: f64,
+}
+
+impl<M: MotorGroup> DriveSideMeasurement<M> {
+    /// `wheel_diameter` should be scaled by any gearing between the motors and the wheels
+    pub fn new(
+        chassis: Arc<Mutex<TankChassis<M>>>,
+        side: ChassisSide,
+        track_width: Length,
+        wheel_diameter: Length,
+    ) -> Self {
+        DriveSideMeasurement {
+            chassis,
+            side,
+            track_width,
+            wheel_diameter,
+            prev_dist: None,
+            noise: 1.0,
+        }
+    }
+
+    /// standard deviation of the travel each update, mm
+    pub fn set_noise(&mut self, noise: f64) {
+        self.noise = noise;
+    }
+}
+
+impl<M: MotorGroup> UkfMeasurement for DriveSideMeasurement<M> {
+    fn read(&mut self) -> Option<f64> {
+        let angle = {
+            let Some(chassis) = self.chassis.try_lock() else {
+                // the travel at the next reading would span more than one update
+                self.prev_dist = None;
+                return None;
+            };
+            match self.side {
+                ChassisSide::Left => chassis.left_angle(),
+                ChassisSide::Right => chassis.right_angle(),
+            }
+        };
+        let dist = angle.arc_length(self.wheel_diameter).as_mm();
+        self.prev_dist.replace(dist).map(|prev| dist - prev)
+    }
+
+    fn expected(&self, state: &State, dt: f64) -> Option<f64> {
+        let offset = match self.side {
+            ChassisSide::Left => -self.track_width.as_mm() / 2.0,
+            ChassisSide::Right => self.track_width.as_mm() / 2.0,
+        };
+        Some(tracker_travel(
+            state,
+            dt,
+            TrackerDirection::Parallel,
+            offset,
+        ))
+    }
+
+    fn variance(&self, _reading: f64) -> f64 {
+        self.noise * self.noise
+    }
+}
+
+impl<I: Imu> UkfMeasurement for ImuHeadingMeasurement<I> {
+    fn read(&mut self) -> Option<f64> {
+        ImuHeadingMeasurement::read(self)
+    }
+
+    fn expected(&self, state: &State, _dt: f64) -> Option<f64> {
+        Some(state[H])
+    }
+
+    fn variance(&self, _reading: f64) -> f64 {
+        ImuHeadingMeasurement::variance(self)
+    }
+
+    fn is_angle(&self) -> bool {
+        true
+    }
+
+    fn set_pose(&mut self, pose: Pose) {
+        ImuHeadingMeasurement::set_pose(self, pose);
+    }
+}
+
+impl<S: DistanceSensor> UkfMeasurement for DistanceMeasurement<S> {
+    fn read(&mut self) -> Option<f64> {
+        DistanceMeasurement::read(self)
+    }
+
+    fn expected(&self, state: &State, _dt: f64) -> Option<f64> {
+        self.distance(state_pose(state))
+    }
+
+    fn variance(&self, reading: f64) -> f64 {
+        DistanceMeasurement::variance(self, reading)
+    }
+}
+
+/// Unscented Kalman filter fusing any number of [`UkfMeasurement`]s.
+///
+/// The state is the pose and the robot's forward and angular velocity, which follow
+/// a constant velocity differential drive model between updates. Sigma points are
+/// spread symmetrically with equal weights, which keeps the covariance positive
+/// definite.
+///
+/// Readings are fused one at a time. A reading further than the gate from what the
+/// filter expects, in standard deviations, is treated as an outlier and ignored.
+pub struct UkfLocaliser {
+    measurements: Vec<Box<dyn UkfMeasurement>>,
+    state: State,
+    covariance: Covariance,
+    process_noise: (f64, f64),
+    spread: (f64, f64),
+    gate: f64,
+    prev_time: Option<Instant>,
+}
+
+impl UkfLocaliser {
+    pub fn new(measurements: Vec<Box<dyn UkfMeasurement>>, init_pose: Pose) -> Self {
+        let mut localiser = UkfLocaliser {
+            measurements,
+            state: State::zeros(),
+            covariance: Covariance::from_diagonal([0.0, 0.0, 0.0, 1.0, 0.01]),
+            process_noise: (2000.0, 20.0),
+            spread: (20.0, 2.0_f64.to_radians()),
+            gate: 4.0,
+            prev_time: None,
+        };
+        localiser.set_pose(init_pose);
+        localiser
+    }
+
+    /// Standard deviation of the unmodelled linear (mm/s²) and angular (rad/s²)
+    /// acceleration.
+    pub fn set_process_noise(&mut self, accel: f64, ang_accel: f64) {
+        self.process_noise = (accel, ang_accel);
+    }
+
+    /// Standard deviation of position and heading (radians) after
+    /// [`Localiser::set_pose`].
+    pub fn set_spread(&mut self, pos: f64, heading: f64) {
+        self.spread = (pos, heading);
+    }
+
+    /// readings further than `gate` standard deviations from expected are ignored
+    pub fn set_gate(&mut self, gate: f64) {
+        self.gate = gate;
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn covariance(&self) -> &Covariance {
+        &self.covariance
+    }
+
+    pub fn twist(&self) -> Twist {
+        Twist::new(
+            LinearVelocity::from_mm_per_sec(self.state[V]),
+            AngularVelocity::from_rad_per_sec(self.state[W]),
+        )
+    }
+
+    // symmetric sigma points, each weighted 1 / 2n
+    fn sigma_points(&self) -> Option<[State; 2 * STATES]> {
+        // a little extra so states known exactly still factorise
+        let jitter = Covariance::identity() * 1e-9;
+        let root = ((self.covariance + jitter) * STATES as f64).cholesky()?;
+        let mut points = [self.state; 2 * STATES];
+        for i in 0..STATES {
+            let column = root.column(i);
+            points[2 * i] += column;
+            points[2 * i + 1] -= column;
+        }
+        Some(points)
+    }
+
+    // mean of sigma points, with headings averaged on the circle
+    fn mean(points: &[State]) -> State {
+        let weight = 1.0 / points.len() as f64;
+        let mut mean = State::zeros();
+        for point in points.iter() {
+            mean += *point * weight;
+        }
+        let heading = Heading::mean(points.iter().map(|point| Heading::new(point[H])));
+        mean[H] = heading.map_or(points[0][H], |h| h.as_rad(AngleSystem::Cartesian));
+        mean
+    }
+
+    fn residual(a: &State, b: &State) -> State {
+        let mut residual = *a - *b;
+        residual[H] = wrap_angle(residual[H]);
+        residual
+    }
+
+    fn predict(&mut self, dt: f64) {
+        let Some(mut points) = self.sigma_points() else {
+            return;
+        };
+        for point in points.iter_mut() {
+            // straight line at the average heading
+            let (sin, cos) = (point[H] + point[W] * dt / 2.0).sin_cos();
+            point[X] += point[V] * dt * cos;
+            point[Y] += point[V] * dt * sin;
+            point[H] = wrap_angle(point[H] + point[W] * dt);
+        }
+
+        let mean = Self::mean(&points);
+        let mut covariance = Covariance::zeros();
+        for point in points.iter() {
+            let residual = Self::residual(point, &mean);
+            covariance += residual * residual.transpose() * (1.0 / points.len() as f64);
+        }
+
+        // random accelerations over the update
+        let (accel, ang_accel) = self.process_noise;
+        let (lin, ang) = (accel * dt, ang_accel * dt);
+        covariance += Covariance::from_diagonal([
+            (lin * dt / 2.0).powi(2),
+            (lin * dt / 2.0).powi(2),
+            (ang * dt / 2.0).powi(2),
+            lin * lin,
+            ang * ang,
+        ]);
+
+        self.state = mean;
+        self.covariance = covariance.symmetrise();
+    }
+
+    fn correct(&mut self, index: usize, reading: f64, dt: f64) {
+        let Some(points) = self.sigma_points() else {
+            return;
+        };
+        let measurement = &self.measurements[index];
+        let mut expected = [0.0; 2 * STATES];
+        for (point, expected) in points.iter().zip(expected.iter_mut()) {
+            match measurement.expected(point, dt) {
+                Some(value) => *expected = value,
+                None => return,
+            }
+        }
+
+        let is_angle = measurement.is_angle();
+        let diff = |a: f64, b: f64| if is_angle { wrap_angle(a - b) } else { a - b };
+        let weight = 1.0 / points.len() as f64;
+        let mean = if is_angle {
+            Heading::mean(expected.iter().map(|e| Heading::new(*e)))
+                .map_or(expected[0], |h| h.as_rad(AngleSystem::Cartesian))
+        } else {
+            expected.iter().sum::<f64>() * weight
+        };
+
+        let mut variance = measurement.variance(reading);
+        let mut cross = State::zeros();
+        for (point, expected) in points.iter().zip(expected.iter()) {
+            let residual = diff(*expected, mean);
+            variance += residual * residual * weight;
+            cross += Self::residual(point, &self.state) * (residual * weight);
+        }
+
+        let innovation = diff(reading, mean);
+        if innovation * innovation > self.gate * self.gate * variance {
+            return;
+        }
+
+        let gain = cross * (1.0 / variance);
+        self.state += gain * innovation;
+        self.state[H] = wrap_angle(self.state[H]);
+        self.covariance = (self.covariance - gain * gain.transpose() * variance).symmetrise();
+    }
+}
+
+impl Localiser for UkfLocaliser {
+    fn pose(&self) -> Pose {
+        state_pose(&self.state)
+    }
+
+    /// Resets the pose and its uncertainty, keeping the velocity estimate.
+    fn set_pose(&mut self, pose: Pose) {
+        self.state[X] = pose.x.as_mm();
+        self.state[Y] = pose.y.as_mm();
+        self.state[H] = wrap_angle(pose.h.as_rad(AngleSystem::Cartesian));
+
+        let (pos, heading) = self.spread;
+        let (v, w) = (self.covariance[(V, V)], self.covariance[(W, W)]);
+        self.covariance =
+            Covariance::from_diagonal([pos * pos, pos * pos, heading * heading, v, w]);
+
+        for measurement in self.measurements.iter_mut() {
+            measurement.set_pose(pose);
+        }
+    }
+
+    async fn update(&mut self) {
+        let now = Instant::now();
+        let dt = self
+            .prev_time
+            .replace(now)
+            .map(|prev_time| now.duration_since(prev_time).as_secs_f64());
+        // no time has passed, so the readings are left to the next update
+        if dt.is_some_and(|dt| dt <= 0.0) {
+            return;
+        }
+        let readings: Vec<Option<f64>> = self.measurements.iter_mut().map(|m| m.read()).collect();
+
+        // first update only sets the references
+        let Some(dt) = dt else {
+            return;
+        };
+
+        self.predict(dt);
+        for (index, reading) in readings.into_iter().enumerate() {
+            if let Some(reading) = reading {
+                self.correct(index, reading, dt);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    float::Float,
+    hal::DistanceSensor,
+    localisation::{
+        field::{Field, SensorMount},
+        Localiser, Pose,
+    },
+    units::Length,
+};
+
+/// Corrects drift by snapping the pose to distance readings of the field walls.
+///
+/// Each sensor's ray is cast from the current pose to find the wall it should be
+/// facing. If that wall runs along x or y, the difference between the expected and
+/// measured distance moves the pose across the wall, so a sensor facing a side wall
+/// fixes x and one facing the far wall fixes y. Sensors correcting the same
+/// coordinate are averaged. Heading is left alone.
+pub struct WallReset {
+    field: Field,
+    min_confidence: f64,
+    min_size: u32,
+    max_range: f64,
+    max_incidence: f64,
+    max_correction: f64,
+}
+
+impl WallReset {
+    pub fn new(field: Field) -> Self {
+        WallReset {
+            field,
+            min_confidence: 0.9,
+            min_size: 50,
+            max_range: 1500.0,
+            max_incidence: 20.0_f64.to_radians(),
+            max_correction: 100.0,
+        }
+    }
+
+    /// Readings with lower confidence or a smaller `relative_size` are ignored, a
+    /// small object is more likely another robot than a wall.
+    pub fn set_min_quality(&mut self, min_confidence: f64, min_size: u32) {
+        self.min_confidence = min_confidence;
+        self.min_size = min_size;
+    }
+
+    /// readings further than this are ignored, mm
+    pub fn set_max_range(&mut self, max_range: f64) {
+        self.max_range = max_range;
+    }
+
+    /// Largest angle between a sensor's ray and the wall's normal, in radians.
+    /// Glancing readings are unreliable.
+    pub fn set_max_incidence(&mut self, max_incidence: f64) {
+        self.max_incidence = max_incidence;
+    }
+
+    /// corrections larger than this are assumed to be something in the way, mm
+    pub fn set_max_correction(&mut self, max_correction: f64) {
+        self.max_correction = max_correction;
+    }
+
+    /// `pose` corrected by whichever readings can be trusted.
+    pub fn correct<S: DistanceSensor>(&self, pose: Pose, sensors: &[(S, SensorMount)]) -> Pose {
+        let (mut x_sum, mut x_count) = (0.0, 0);
+        let (mut y_sum, mut y_count) = (0.0, 0);
+
+        for (sensor, mount) in sensors.iter() {
+            let Some(reading) = sensor.object() else {
+                continue;
+            };
+            if reading.confidence < self.min_confidence
+                || reading.relative_size < self.min_size
+                || reading.distance > self.max_range
+            {
+                continue;
+            }
+
+            let (origin, angle) = mount.ray(pose);
+            let Some((expected, wall)) = self.field.raycast(origin, angle) else {
+                continue;
+            };
+            let (sin, cos) = angle.sin_cos();
+            let error = expected - reading.distance;
+
+            if wall.start.0 == wall.end.0 {
+                // runs along y, so the normal is along x
+                if sin.abs() > self.max_incidence.sin() {
+                    continue;
+                }
+                let correction = error * cos;
+                if correction.abs() <= self.max_correction {
+                    x_sum += correction;
+                    x_count += 1;
+                }
+            } else if wall.start.1 == wall.end.1 {
+                if cos.abs() > self.max_incidence.sin() {
+                    continue;
+                }
+                let correction = error * sin;
+                if correction.abs() <= self.max_correction {
+                    y_sum += correction;
+                    y_count += 1;
+                }
+            }
+        }
+
+        let mut pose = pose;
+        if x_count > 0 {
+            pose.x += Length::from_mm(x_sum / x_count as f64);
+        }
+        if y_count > 0 {
+            pose.y += Length::from_mm(y_sum / y_count as f64);
+        }
+        pose
+    }
+
+    /// Corrects `localiser`'s pose in place, see [`WallReset::correct`].
+    pub fn apply<L: Localiser, S: DistanceSensor>(
+        &self,
+        localiser: &mut L,
+        sensors: &[(S, SensorMount)],
+    ) {
+        let pose = self.correct(localiser.pose(), sensors);
+        localiser.set_pose(pose);
+    }
+}
@@ -0,0 +1,206 @@
+//! Small fixed-size matrices for the filters.
+//!
+//! Sizes are checked at compile time and everything lives on the stack, so there is
+//! no allocation in the update loop.
+
+use core::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+use crate::float::Float;
+
+/// `R` rows by `C` columns, stored row major
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Matrix<const R: usize, const C: usize>([[f64; C]; R]);
+
+/// Column vector
+pub type Vector<const N: usize> = Matrix<N, 1>;
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    pub fn new(rows: [[f64; C]; R]) -> Self {
+        Matrix(rows)
+    }
+
+    pub fn zeros() -> Self {
+        Matrix([[0.0; C]; R])
+    }
+
+    pub fn rows(&self) -> &[[f64; C]; R] {
+        &self.0
+    }
+
+    pub fn transpose(&self) -> Matrix<C, R> {
+        let mut out = Matrix::zeros();
+        for i in 0..R {
+            for j in 0..C {
+                out.0[j][i] = self.0[i][j];
+            }
+        }
+        out
+    }
+
+    pub fn column(&self, j: usize) -> Vector<R> {
+        let mut out = Vector::zeros();
+        for i in 0..R {
+            out.0[i][0] = self.0[i][j];
+        }
+        out
+    }
+
+    fn map(mut self, f: impl Fn(f64) -> f64) -> Self {
+        for row in self.0.iter_mut() {
+            for value in row.iter_mut() {
+                *value = f(*value);
+            }
+        }
+        self
+    }
+
+    fn zip(mut self, other: Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        for (row, other) in self.0.iter_mut().zip(other.0.iter()) {
+            for (value, other) in row.iter_mut().zip(other.iter()) {
+                *value = f(*value, *other);
+            }
+        }
+        self
+    }
+}
+
+impl<const N: usize> Matrix<N, N> {
+    pub fn identity() -> Self {
+        Matrix::from_diagonal([1.0; N])
+    }
+
+    pub fn from_diagonal(diagonal: [f64; N]) -> Self {
+        let mut out = Matrix::zeros();
+        for (i, value) in diagonal.into_iter().enumerate() {
+            out.0[i][i] = value;
+        }
+        out
+    }
+
+    /// Averages with the transpose, removing asymmetry left by rounding.
+    pub fn symmetrise(&self) -> Self {
+        (*self + self.transpose()) * 0.5
+    }
+
+    /// Lower triangular `L` with `L * Lᵀ = self`, `None` unless positive definite.
+    pub fn cholesky(&self) -> Option<Self> {
+        let mut l = Matrix::zeros();
+        for i in 0..N {
+            for j in 0..=i {
+                let mut sum = self.0[i][j];
+                for k in 0..j {
+                    sum -= l.0[i][k] * l.0[j][k];
+                }
+                if i == j {
+                    if sum <= 0.0 || sum.is_nan() {
+                        return None;
+                    }
+                    l.0[i][i] = sum.sqrt();
+                } else {
+                    l.0[i][j] = sum / l.0[j][j];
+                }
+            }
+        }
+        Some(l)
+    }
+}
+
+impl<const N: usize> Vector<N> {
+    pub fn from_array(values: [f64; N]) -> Self {
+        let mut out = Vector::zeros();
+        for (i, value) in values.into_iter().enumerate() {
+            out.0[i][0] = value;
+        }
+        out
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        (0..N).map(|i| self.0[i][0] * other.0[i][0]).sum()
+    }
+}
+
+impl<const R: usize, const C: usize> Index<(usize, usize)> for Matrix<R, C> {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.0[i][j]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<R, C> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.0[i][j]
+    }
+}
+
+impl<const N: usize> Index<usize> for Vector<N> {
+    type Output = f64;
+
+    fn index(&self, i: usize) -> &f64 {
+        &self.0[i][0]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Vector<N> {
+    fn index_mut(&mut self, i: usize) -> &mut f64 {
+        &mut self.0[i][0]
+    }
+}
+
+impl<const R: usize, const C: usize> Add for Matrix<R, C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a + b)
+    }
+}
+
+impl<const R: usize, const C: usize> AddAssign for Matrix<R, C> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const R: usize, const C: usize> Sub for Matrix<R, C> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a - b)
+    }
+}
+
+impl<const R: usize, const C: usize> SubAssign for Matrix<R, C> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const R: usize, const C: usize> Neg for Matrix<R, C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.map(|a| -a)
+    }
+}
+
+impl<const R: usize, const C: usize> Mul<f64> for Matrix<R, C> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        self.map(|a| a * rhs)
+    }
+}
+
+impl<const R: usize, const K: usize, const C: usize> Mul<Matrix<K, C>> for Matrix<R, K> {
+    type Output = Matrix<R, C>;
+
+    fn mul(self, rhs: Matrix<K, C>) -> Matrix<R, C> {
+        let mut out = Matrix::zeros();
+        for i in 0..R {
+            for j in 0..C {
+                out.0[i][j] = (0..K).map(|k| self.0[i][k] * rhs.0[k][j]).sum();
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,196 @@
+use core::time::Duration;
+
+use crate::float::Float;
+
+// a filter of 1 would hold the derivative at zero forever
+const MAX_DERIVATIVE_FILTER: f64 = 0.99;
+
+/// PID controller with integral anti-windup and a filtered derivative.
+///
+/// All options default to off, which gives a plain PID.
+#[derive(Clone)]
+pub struct Pid {
+    kp: f64,
+    ki: f64,
+    kd: f64,
+
+    output_limit: f64,
+    integral_limit: f64,
+    reset_on_sign_change: bool,
+    derivative_filter: f64,
+    derivative_on_measurement: bool,
+
+    integral: f64,
+    derivative: f64,
+    prev_error: Option<f64>,
+    prev_input: Option<f64>,
+}
+
+impl Pid {
+    pub fn new(kp: f64, ki: f64, kd: f64) -> Self {
+        Pid {
+            kp,
+            ki,
+            kd,
+            output_limit: f64::INFINITY,
+            integral_limit: f64::INFINITY,
+            reset_on_sign_change: false,
+            derivative_filter: 0.0,
+            derivative_on_measurement: false,
+            integral: 0.0,
+            derivative: 0.0,
+            prev_error: None,
+            prev_input: None,
+        }
+    }
+
+    pub fn set_gains(&mut self, kp: f64, ki: f64, kd: f64) {
+        self.kp = kp;
+        self.ki = ki;
+        self.kd = kd;
+    }
+
+    /// output is clamped to [-limit, limit]
+    pub fn set_output_limit(&mut self, limit: f64) {
+        self.output_limit = limit;
+    }
+
+    /// the integral term (ki * integral) is clamped to [-limit, limit]
+    pub fn set_integral_limit(&mut self, limit: f64) {
+        self.integral_limit = limit;
+    }
+
+    /// clear the integral when the error crosses zero
+    pub fn set_reset_on_sign_change(&mut self, reset: bool) {
+        self.reset_on_sign_change = reset;
+    }
+
+    /// Low pass filter on the derivative, from 0 (unfiltered) to just below 1.
+    ///
+    /// Each update keeps this fraction of the previous derivative. Clamped to
+    /// [0, 0.99].
+    pub fn set_derivative_filter(&mut self, alpha: f64) {
+        self.derivative_filter = alpha.clamp(0.0, MAX_DERIVATIVE_FILTER);
+    }
+
+    /// Take the derivative of the measurement instead of the error, which avoids
+    /// a kick when the target changes. Only applies to [`Pid::update_measured`].
+    pub fn set_derivative_on_measurement(&mut self, enabled: bool) {
+        self.derivative_on_measurement = enabled;
+    }
+
+    /// `dt` is the time since the last update in seconds
+    pub fn update(&mut self, error: f64, dt: f64) -> f64 {
+        self.step(error, error, dt)
+    }
+
+    pub fn update_measured(&mut self, target: f64, measurement: f64, dt: f64) -> f64 {
+        let error = target - measurement;
+        if self.derivative_on_measurement {
+            // d(error)/dt = -d(measurement)/dt with a constant target
+            self.step(error, -measurement, dt)
+        } else {
+            self.step(error, error, dt)
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.derivative = 0.0;
+        self.prev_error = None;
+        self.prev_input = None;
+    }
+
+    fn step(&mut self, error: f64, derivative_input: f64, dt: f64) -> f64 {
+        if self.reset_on_sign_change
+            && self
+                .prev_error
+                .is_some_and(|prev| Float::signum(prev) != Float::signum(error))
+        {
+            self.integral = 0.0;
+        }
+
+        let raw_derivative = match self.prev_input {
+            Some(prev) if dt > 0.0 => (derivative_input - prev) / dt,
+            _ => 0.0,
+        };
+        if self.prev_input.is_some() {
+            self.derivative = self.derivative_filter * self.derivative
+                + (1.0 - self.derivative_filter) * raw_derivative;
+        }
+        self.prev_input = Some(derivative_input);
+        self.prev_error = Some(error);
+
+        let unintegrated = self.kp * error + self.kd * self.derivative;
+
+        // only integrate if it would not push a saturated output further
+        let integral = self.integral + error * dt;
+        let output = unintegrated + self.i_term(integral);
+        let saturated =
+            Float::abs(output) > self.output_limit && Float::signum(error) == Float::signum(output);
+        if !saturated {
+            self.integral = integral;
+        }
+        if self.ki != 0.0 {
+            // keep the stored integral consistent with the clamped term
+            self.integral = self.i_term(self.integral) / self.ki;
+        }
+
+        (unintegrated + self.i_term(self.integral)).clamp(-self.output_limit, self.output_limit)
+    }
+
+    fn i_term(&self, integral: f64) -> f64 {
+        (self.ki * integral).clamp(-self.integral_limit, self.integral_limit)
+    }
+}
+
+/// Detects when an error has stayed small and slow for a period of time.
+#[derive(Clone)]
+pub struct Settle {
+    error_threshold: f64,
+    velocity_threshold: f64,
+    window: Duration,
+    prev_error: Option<f64>,
+    settled_for: Duration,
+}
+
+impl Settle {
+    /// Settled once |error| < `error_threshold` and |d(error)/dt| < `velocity_threshold`
+    /// have held for `window`.
+    pub fn new(error_threshold: f64, velocity_threshold: f64, window: Duration) -> Self {
+        Settle {
+            error_threshold,
+            velocity_threshold,
+            window,
+            prev_error: None,
+            settled_for: Duration::ZERO,
+        }
+    }
+
+    /// `dt` is the time since the last update in seconds
+    pub fn update(&mut self, error: f64, dt: f64) -> bool {
+        let velocity = match self.prev_error {
+            Some(prev) if dt > 0.0 => (error - prev) / dt,
+            _ => 0.0,
+        };
+        self.prev_error = Some(error);
+
+        if Float::abs(error) < self.error_threshold
+            && Float::abs(velocity) < self.velocity_threshold
+        {
+            self.settled_for += Duration::from_secs_f64(dt.max(0.0));
+        } else {
+            self.settled_for = Duration::ZERO;
+        }
+        self.settled()
+    }
+
+    pub fn settled(&self) -> bool {
+        self.settled_for >= self.window
+    }
+
+    pub fn reset(&mut self) {
+        self.prev_error = None;
+        self.settled_for = Duration::ZERO;
+    }
+}
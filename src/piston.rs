@@ -1,17 +1,13 @@
-use vexide::prelude::AdiDigitalOut;
+use crate::hal::DigitalOut;
 
-pub struct Piston {
-    adi_out: AdiDigitalOut,
+pub struct Piston<D: DigitalOut> {
+    adi_out: D,
     activated: bool,
 }
 
-impl Piston {
-    pub fn new(mut adi: AdiDigitalOut, initial: bool) -> Self {
-        if initial {
-            adi.set_high().ok();
-        } else {
-            adi.set_low().ok();
-        }
+impl<D: DigitalOut> Piston<D> {
+    pub fn new(mut adi: D, initial: bool) -> Self {
+        adi.set_level(initial);
         Piston {
             adi_out: adi,
             activated: initial,
@@ -32,10 +28,6 @@ impl Piston {
     }
 
     fn update(&mut self) {
-        if self.activated {
-            self.adi_out.set_high().ok();
-        } else {
-            self.adi_out.set_low().ok();
-        }
+        self.adi_out.set_level(self.activated);
     }
 }
@@ -0,0 +1,152 @@
+//! Runtime primitives used by the control loops.
+//!
+//! With the `vexide` feature these are vexide's. Otherwise time is simulated: the
+//! clock only moves when something sleeps or calls [`advance`], so control loops
+//! run as fast as the host allows and give the same result every run.
+
+#[cfg(not(feature = "vexide"))]
+pub use host::*;
+#[cfg(feature = "vexide")]
+pub use vexide::{
+    async_runtime::time::{sleep, sleep_until},
+    core::{sync::Mutex, time::Instant},
+};
+
+#[cfg(not(feature = "vexide"))]
+mod host {
+    use core::{
+        cell::UnsafeCell,
+        future::Future,
+        ops::{Add, Deref, DerefMut},
+        pin::Pin,
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    // simulated time since start, in nanoseconds
+    static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+    /// moves the simulated clock forward
+    pub fn advance(duration: Duration) {
+        CLOCK.fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    pub struct Instant(Duration);
+
+    impl Instant {
+        pub fn now() -> Self {
+            Instant(Duration::from_nanos(CLOCK.load(Ordering::SeqCst)))
+        }
+
+        pub fn duration_since(&self, earlier: Instant) -> Duration {
+            self.0.saturating_sub(earlier.0)
+        }
+
+        pub fn elapsed(&self) -> Duration {
+            Instant::now().duration_since(*self)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+
+        fn add(self, rhs: Duration) -> Instant {
+            Instant(self.0 + rhs)
+        }
+    }
+
+    /// Advances the simulated clock to `deadline` if it has not been reached.
+    pub async fn sleep_until(deadline: Instant) {
+        let now = Instant::now();
+        if deadline > now {
+            advance(deadline.duration_since(now));
+        }
+        YieldNow(false).await;
+    }
+
+    pub async fn sleep(duration: Duration) {
+        sleep_until(Instant::now() + duration).await;
+    }
+
+    /// gives other futures a chance to run
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Async mutex with the same interface as vexide's.
+    pub struct Mutex<T: ?Sized> {
+        locked: AtomicBool,
+        data: UnsafeCell<T>,
+    }
+
+    // SAFETY: access to `data` is guarded by `locked`
+    unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+    unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+    impl<T> Mutex<T> {
+        pub const fn new(data: T) -> Self {
+            Mutex {
+                locked: AtomicBool::new(false),
+                data: UnsafeCell::new(data),
+            }
+        }
+    }
+
+    impl<T: ?Sized> Mutex<T> {
+        pub async fn lock(&self) -> MutexGuard<'_, T> {
+            loop {
+                if let Some(guard) = self.try_lock() {
+                    return guard;
+                }
+                YieldNow(false).await;
+            }
+        }
+
+        pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+            self.locked
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .ok()
+                .map(|_| MutexGuard { mutex: self })
+        }
+    }
+
+    pub struct MutexGuard<'a, T: ?Sized> {
+        mutex: &'a Mutex<T>,
+    }
+
+    impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: the guard holds the lock
+            unsafe { &*self.mutex.data.get() }
+        }
+    }
+
+    impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            // SAFETY: the guard holds the lock
+            unsafe { &mut *self.mutex.data.get() }
+        }
+    }
+
+    impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+        fn drop(&mut self) {
+            self.mutex.locked.store(false, Ordering::Release);
+        }
+    }
+}
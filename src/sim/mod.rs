@@ -0,0 +1,321 @@
+//! Host simulation of a tank drive.
+//!
+//! [`TankSim`] reads the commands given to [`MockMotor`]s, integrates the robot at a
+//! fixed timestep and writes back simulated motor, tracking wheel and inertial sensor
+//! readings, so the chassis, controllers and localisers run unchanged against it.
+
+pub mod motor;
+
+use alloc::vec::Vec;
+use core::{
+    f64::consts,
+    future::Future,
+    pin::pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    time::Duration,
+};
+
+pub use crate::localisation::TrackerDirection;
+use crate::{
+    float::Float,
+    hal::{
+        mock::{MockEncoder, MockImu, MockMotor},
+        MotorGroup, MAX_VOLTAGE,
+    },
+    localisation::{AngleSystem, Pose},
+    rt::Instant,
+    sim::motor::{firmware_voltage, DcMotor},
+    tank_chassis::model::TankModel,
+    units::Length,
+};
+
+struct SimTracker {
+    encoder: MockEncoder,
+    direction: TrackerDirection,
+    offset: f64,
+    wheel_diameter: f64,
+    travel: f64,
+}
+
+struct SimImu {
+    imu: MockImu,
+    scale: f64,
+    // radians counterclockwise since it was added
+    turn: f64,
+}
+
+/// Kinematic and force model of a tank drive.
+///
+/// Each side pushes with up to half the model's force, falling off linearly to zero
+/// at `lin_speed`, so full voltage settles at `lin_speed`. Alternatively each motor
+/// can be simulated with a [`DcMotor`]. Wheels do not slip.
+pub struct TankSim {
+    mass: f64,
+    inertia: f64,
+    track_width: f64,
+    lin_speed: f64,
+    force: f64,
+    wheel_diameter: f64,
+    drive_motor: Option<DcMotor>,
+
+    left: Vec<MockMotor>,
+    right: Vec<MockMotor>,
+    trackers: Vec<SimTracker>,
+    imus: Vec<SimImu>,
+
+    pose: Pose,
+    lin_vel: f64,
+    ang_vel: f64,
+    left_travel: f64,
+    right_travel: f64,
+
+    timestep: Duration,
+    time: Instant,
+}
+
+impl TankSim {
+    /// `left` and `right` should be clones of the motors given to the chassis.
+    /// `wheel_diameter` should be scaled by any gearing between the motors and the
+    /// wheels.
+    pub fn new(
+        model: &TankModel,
+        left: Vec<MockMotor>,
+        right: Vec<MockMotor>,
+        wheel_diameter: Length,
+    ) -> Self {
+        let track_width = model.track_width().as_mm();
+        let sim = TankSim {
+            mass: model.mass(),
+            // uniform square plate the width of the track
+            inertia: model.mass() * track_width.powi(2) / 6.0,
+            track_width,
+            lin_speed: model.lin_speed().as_mm_per_sec(),
+            force: model.force(),
+            wheel_diameter: wheel_diameter.as_mm(),
+            drive_motor: None,
+            left,
+            right,
+            trackers: Vec::new(),
+            imus: Vec::new(),
+            pose: Pose::identity(),
+            lin_vel: 0.0,
+            ang_vel: 0.0,
+            left_travel: 0.0,
+            right_travel: 0.0,
+            timestep: Duration::from_millis(1),
+            time: Instant::now(),
+        };
+        sim.write_readings();
+        sim
+    }
+
+    /// moment of inertia about the tracking centre
+    pub fn set_inertia(&mut self, inertia: f64) {
+        self.inertia = inertia;
+    }
+
+    /// Drives each motor with a DC motor model instead of the model's force.
+    ///
+    /// Needs mass in kg and lengths in mm.
+    pub fn set_drive_motor(&mut self, drive_motor: Option<DcMotor>) {
+        self.drive_motor = drive_motor;
+    }
+
+    pub fn set_timestep(&mut self, timestep: Duration) {
+        self.timestep = timestep;
+    }
+
+    /// Moves the robot without changing any sensor readings.
+    pub fn set_pose(&mut self, pose: Pose) {
+        self.pose = pose;
+    }
+
+    /// Simulates a tracking wheel reporting to `encoder`, `offset` from the tracking
+    /// centre perpendicular to its travel.
+    pub fn add_tracker(
+        &mut self,
+        encoder: MockEncoder,
+        direction: TrackerDirection,
+        offset: Length,
+        wheel_diameter: Length,
+    ) {
+        encoder.set_position(0.0);
+        self.trackers.push(SimTracker {
+            encoder,
+            direction,
+            offset: offset.as_mm(),
+            wheel_diameter: wheel_diameter.as_mm(),
+            travel: 0.0,
+        });
+    }
+
+    /// Simulates an inertial sensor reporting to `imu`, zeroed now, which reads
+    /// `scale` times the true rotation.
+    pub fn add_imu(&mut self, imu: MockImu, scale: f64) {
+        imu.set_rotation(Some(0.0));
+        self.imus.push(SimImu {
+            imu,
+            scale,
+            turn: 0.0,
+        });
+    }
+
+    pub fn pose(&self) -> Pose {
+        self.pose
+    }
+
+    pub fn lin_vel(&self) -> f64 {
+        self.lin_vel
+    }
+
+    /// radians per second, counterclockwise positive
+    pub fn ang_vel(&self) -> f64 {
+        self.ang_vel
+    }
+
+    /// Integrates one timestep, without regard to the clock.
+    pub fn step(&mut self) {
+        let dt = self.timestep.as_secs_f64();
+        let half_track = self.track_width / 2.0;
+        let left_vel = self.lin_vel - self.ang_vel * half_track;
+        let right_vel = self.lin_vel + self.ang_vel * half_track;
+
+        let left_force = self.side_force(&self.left, left_vel, self.left_travel);
+        let right_force = self.side_force(&self.right, right_vel, self.right_travel);
+
+        let lin_accel = (left_force + right_force) / self.mass;
+        let ang_accel = (right_force - left_force) * half_track / self.inertia;
+        let lin_vel = self.lin_vel + lin_accel * dt;
+        let ang_vel = self.ang_vel + ang_accel * dt;
+
+        // average of the start and end velocities over the step
+        let dist = (self.lin_vel + lin_vel) / 2.0 * dt;
+        let d_theta = (self.ang_vel + ang_vel) / 2.0 * dt;
+        self.lin_vel = lin_vel;
+        self.ang_vel = ang_vel;
+
+        let theta = self.pose.h.as_rad(AngleSystem::Cartesian);
+        let (sin, cos) = (theta + d_theta / 2.0).sin_cos();
+        self.pose.x += Length::from_mm(dist * cos);
+        self.pose.y += Length::from_mm(dist * sin);
+        self.pose.h.set_rad(theta + d_theta, AngleSystem::Cartesian);
+
+        self.left_travel += dist - d_theta * half_track;
+        self.right_travel += dist + d_theta * half_track;
+        for tracker in self.trackers.iter_mut() {
+            tracker.travel += match tracker.direction {
+                TrackerDirection::Parallel => dist + d_theta * tracker.offset,
+                TrackerDirection::Lateral => -d_theta * tracker.offset,
+            };
+        }
+        for imu in self.imus.iter_mut() {
+            imu.turn += d_theta;
+        }
+
+        self.time = self.time + self.timestep;
+        self.write_readings();
+    }
+
+    /// Integrates up to the current time of the clock in [`crate::rt`].
+    pub fn update(&mut self) {
+        while self.time + self.timestep <= Instant::now() {
+            self.step();
+        }
+    }
+
+    /// Runs `future` to completion, stepping the simulation whenever it yields.
+    ///
+    /// Control loops advance the simulated clock when they sleep, so the robot moves
+    /// between iterations as it would in real time.
+    pub fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                self.update();
+                return output;
+            }
+            self.update();
+        }
+    }
+
+    // force from one side of the drive, given its current command
+    fn side_force(&self, motors: &[MockMotor], vel: f64, travel: f64) -> f64 {
+        let Some(motor) = motors.first() else {
+            return 0.0;
+        };
+        let volts = firmware_voltage(
+            motor.target(),
+            travel / self.dist_per_rev() * 360.0,
+            vel / self.dist_per_rev() * 60.0,
+            motor.max_rpm(),
+            // holding is treated as braking
+            travel / self.dist_per_rev() * 360.0,
+        );
+
+        match &self.drive_motor {
+            Some(dc_motor) => {
+                let radius = self.wheel_diameter / 2.0;
+                // N at the wheel, to kg mm / s^2
+                let force = dc_motor.torque(volts, vel / radius) / (radius / 1000.0) * 1000.0;
+                force * motors.len() as f64
+            }
+            None => volts.map_or(0.0, |volts| {
+                self.force / 2.0 * (volts / MAX_VOLTAGE - vel / self.lin_speed)
+            }),
+        }
+    }
+
+    fn dist_per_rev(&self) -> f64 {
+        consts::PI * self.wheel_diameter
+    }
+
+    fn write_readings(&self) {
+        let half_track = self.track_width / 2.0;
+        let sides = [
+            (
+                &self.left,
+                self.left_travel,
+                self.lin_vel - self.ang_vel * half_track,
+            ),
+            (
+                &self.right,
+                self.right_travel,
+                self.lin_vel + self.ang_vel * half_track,
+            ),
+        ];
+        for (motors, travel, vel) in sides {
+            for motor in motors {
+                motor.set_measured(
+                    travel / self.dist_per_rev() * 360.0,
+                    vel / self.dist_per_rev() * 60.0,
+                );
+            }
+        }
+        for tracker in self.trackers.iter() {
+            let circumference = consts::PI * tracker.wheel_diameter;
+            tracker
+                .encoder
+                .set_position(tracker.travel / circumference * 360.0);
+        }
+        for imu in self.imus.iter() {
+            // clockwise degrees
+            imu.imu
+                .set_rotation(Some(-imu.scale * imu.turn.to_degrees()));
+        }
+    }
+}
+
+/// Waker that does nothing, for polling futures by hand.
+pub fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+    // SAFETY: the vtable functions do nothing with the data pointer
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+}
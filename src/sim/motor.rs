@@ -0,0 +1,236 @@
+//! DC motor model of the V5 smart motor and its firmware controllers.
+
+use core::f64::consts;
+
+use crate::{
+    float::Float,
+    hal::{mock::MockMotor, BrakeMode, MotorGroup, MotorTarget, MAX_VOLTAGE},
+};
+
+// V5 motor at the output of the 100 rpm cartridge
+const RED_STALL_TORQUE: f64 = 2.1; // N m, at the current limit
+const RED_FREE_SPEED: f64 = 100.0 * consts::TAU / 60.0; // rad/s at 12 V
+const CURRENT_LIMIT: f64 = 2.5; // A
+const RESISTANCE: f64 = 2.4; // ohms, gives the rated 11 W peak output
+
+// proportional gain of the firmware velocity controller, volts per unit of error as
+// a fraction of the maximum rpm
+const VELOCITY_KP: f64 = 4.0 * MAX_VOLTAGE;
+// proportional gain of the firmware position controller, rpm per degree
+const POSITION_KP: f64 = 2.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Gearset {
+    /// 36:1, 100 rpm
+    Red,
+    /// 18:1, 200 rpm
+    Green,
+    /// 6:1, 600 rpm
+    Blue,
+}
+
+impl Gearset {
+    pub fn max_rpm(&self) -> f64 {
+        match self {
+            Gearset::Red => 100.0,
+            Gearset::Green => 200.0,
+            Gearset::Blue => 600.0,
+        }
+    }
+}
+
+/// Brushed DC motor with a current limit and optional friction.
+///
+/// Torque is in N m and velocity in rad/s at the output shaft.
+#[derive(Clone, Debug)]
+pub struct DcMotor {
+    torque_constant: f64,
+    back_emf_constant: f64,
+    resistance: f64,
+    current_limit: f64,
+    viscous: f64,
+    coulomb: f64,
+}
+
+impl DcMotor {
+    /// V5 smart motor with the given cartridge, without friction
+    pub fn new(gearset: Gearset) -> Self {
+        let ratio = gearset.max_rpm() / Gearset::Red.max_rpm();
+        DcMotor {
+            torque_constant: RED_STALL_TORQUE / CURRENT_LIMIT / ratio,
+            back_emf_constant: MAX_VOLTAGE / RED_FREE_SPEED / ratio,
+            resistance: RESISTANCE,
+            current_limit: CURRENT_LIMIT,
+            viscous: 0.0,
+            coulomb: 0.0,
+        }
+    }
+
+    /// amps
+    pub fn set_current_limit(&mut self, current_limit: f64) {
+        self.current_limit = current_limit;
+    }
+
+    /// `viscous` is N m per rad/s, `coulomb` is N m
+    pub fn set_friction(&mut self, viscous: f64, coulomb: f64) {
+        self.viscous = viscous;
+        self.coulomb = coulomb;
+    }
+
+    /// speed with no load at `MAX_VOLTAGE`, ignoring friction
+    pub fn free_speed(&self) -> f64 {
+        MAX_VOLTAGE / self.back_emf_constant
+    }
+
+    /// torque at zero speed and `MAX_VOLTAGE`, after the current limit
+    pub fn stall_torque(&self) -> f64 {
+        self.torque_constant * self.current(Some(MAX_VOLTAGE), 0.0)
+    }
+
+    /// Current drawn at `velocity`, `None` volts when coasting with the windings open.
+    pub fn current(&self, volts: Option<f64>, velocity: f64) -> f64 {
+        volts.map_or(0.0, |volts| {
+            ((volts - self.back_emf_constant * velocity) / self.resistance)
+                .clamp(-self.current_limit, self.current_limit)
+        })
+    }
+
+    /// Output torque after friction. Coulomb friction is ignored at rest.
+    pub fn torque(&self, volts: Option<f64>, velocity: f64) -> f64 {
+        let friction = self.viscous * velocity
+            + if velocity == 0.0 {
+                0.0
+            } else {
+                self.coulomb * Float::signum(velocity)
+            };
+        self.torque_constant * self.current(volts, velocity) - friction
+    }
+
+    /// Velocity after `dt` seconds driving a load of `inertia` (kg m^2).
+    ///
+    /// Coulomb friction holds the motor at rest and stops it rather than reversing it.
+    pub fn step_velocity(&self, volts: Option<f64>, velocity: f64, inertia: f64, dt: f64) -> f64 {
+        if velocity == 0.0 {
+            let torque = self.torque(volts, 0.0);
+            if Float::abs(torque) <= self.coulomb {
+                return 0.0;
+            }
+            let torque = torque - self.coulomb * Float::signum(torque);
+            return torque / inertia * dt;
+        }
+        let next = velocity + self.torque(volts, velocity) / inertia * dt;
+        // friction alone can't reverse the motor
+        if Float::signum(next) != Float::signum(velocity)
+            && Float::abs(self.torque(volts, 0.0)) <= self.coulomb
+        {
+            0.0
+        } else {
+            next
+        }
+    }
+}
+
+/// Voltage the motor firmware applies to follow `target`, `None` when coasting.
+///
+/// `position` is in degrees and `velocity` in rpm. `hold` is the position to hold
+/// when braking with [`BrakeMode::Hold`].
+pub fn firmware_voltage(
+    target: MotorTarget,
+    position: f64,
+    velocity: f64,
+    max_rpm: f64,
+    hold: f64,
+) -> Option<f64> {
+    let track_position = |deg: f64, rpm: f64| {
+        let rpm = Float::abs(rpm).min(max_rpm);
+        let target = ((deg - position) * POSITION_KP).clamp(-rpm, rpm);
+        track_velocity(target, velocity, max_rpm)
+    };
+    match target {
+        MotorTarget::Voltage(volts) => Some(volts.clamp(-MAX_VOLTAGE, MAX_VOLTAGE)),
+        MotorTarget::Velocity(rpm) => Some(track_velocity(
+            rpm.clamp(-max_rpm, max_rpm),
+            velocity,
+            max_rpm,
+        )),
+        MotorTarget::Position(deg, rpm) => Some(track_position(deg, rpm)),
+        MotorTarget::Brake(BrakeMode::Coast) => None,
+        MotorTarget::Brake(BrakeMode::Brake) => Some(track_velocity(0.0, velocity, max_rpm)),
+        MotorTarget::Brake(BrakeMode::Hold) => Some(track_position(hold, max_rpm)),
+    }
+}
+
+// feedforward plus proportional feedback, both rpm
+fn track_velocity(target: f64, velocity: f64, max_rpm: f64) -> f64 {
+    let volts = (target * MAX_VOLTAGE + VELOCITY_KP * (target - velocity)) / max_rpm;
+    volts.clamp(-MAX_VOLTAGE, MAX_VOLTAGE)
+}
+
+/// A single simulated motor driving a load, such as an arm joint.
+///
+/// Reads the commands given to a [`MockMotor`] and writes back its position and
+/// velocity on each step.
+pub struct SimMotor {
+    motor: DcMotor,
+    device: MockMotor,
+    inertia: f64,
+    position: f64,
+    velocity: f64,
+    hold: Option<f64>,
+}
+
+impl SimMotor {
+    /// `device` should be a clone of the motor given to the mechanism, `inertia` is
+    /// the load on the output shaft in kg m^2
+    pub fn new(motor: DcMotor, device: MockMotor, inertia: f64) -> Self {
+        device.set_measured(0.0, 0.0);
+        SimMotor {
+            motor,
+            device,
+            inertia,
+            position: 0.0,
+            velocity: 0.0,
+            hold: None,
+        }
+    }
+
+    /// radians
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// radians per second
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Integrates `dt` seconds of the current command.
+    pub fn step(&mut self, dt: f64) {
+        let position_deg = self.position.to_degrees();
+        let target = self.device.target();
+        let hold = match target {
+            MotorTarget::Brake(BrakeMode::Hold) => *self.hold.get_or_insert(position_deg),
+            _ => {
+                self.hold = None;
+                position_deg
+            }
+        };
+        let volts = firmware_voltage(
+            target,
+            position_deg,
+            self.velocity * 60.0 / consts::TAU,
+            self.device.max_rpm(),
+            hold,
+        );
+
+        let velocity = self
+            .motor
+            .step_velocity(volts, self.velocity, self.inertia, dt);
+        self.position += (self.velocity + velocity) / 2.0 * dt;
+        self.velocity = velocity;
+        self.device.set_measured(
+            self.position.to_degrees(),
+            self.velocity * 60.0 / consts::TAU,
+        );
+    }
+}
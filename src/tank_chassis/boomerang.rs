@@ -0,0 +1,156 @@
+use core::time::Duration;
+
+use crate::{
+    float::Float,
+    hal::{BrakeMode, MotorGroup},
+    localisation::{AngleSystem, Heading, Localiser, Pose},
+    pid::{Pid, Settle},
+    rt::{sleep_until, Instant, Mutex},
+    tank_chassis::TankChassis,
+};
+
+const UPDATE_PERIOD: Duration = Duration::from_millis(10);
+
+/// Drives to a pose by chasing a carrot point behind the target along its heading.
+///
+/// The carrot starts `lead` times the remaining distance behind the target and
+/// slides onto it as the robot approaches, so the robot arrives facing the target
+/// heading. Once within the close distance only the heading is corrected.
+pub struct MoveToPose {
+    target: Pose,
+    linear: Pid,
+    angular: Pid,
+    lead: f64,
+    max_speed: f64,
+    close_dist: f64,
+    dist_settle: Settle,
+    heading_settle: Settle,
+    timeout: Duration,
+}
+
+impl MoveToPose {
+    /// `linear` acts on distance and `angular` on radians, both output a fraction of
+    /// the maximum voltage.
+    pub fn new(target: Pose, linear: Pid, angular: Pid) -> Self {
+        MoveToPose {
+            target,
+            linear,
+            angular,
+            lead: 0.6,
+            max_speed: 1.0,
+            close_dist: 75.0,
+            dist_settle: Settle::new(10.0, 20.0, Duration::from_millis(250)),
+            heading_settle: Settle::new(
+                2.0_f64.to_radians(),
+                10.0_f64.to_radians(),
+                Duration::from_millis(250),
+            ),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    pub fn set_target(&mut self, target: Pose) {
+        self.target = target;
+    }
+
+    /// carrot distance behind the target, as a fraction of the distance to the target
+    pub fn set_lead(&mut self, lead: f64) {
+        self.lead = lead;
+    }
+
+    /// fraction of the maximum voltage
+    pub fn set_max_speed(&mut self, max_speed: f64) {
+        self.max_speed = max_speed;
+    }
+
+    /// distance from the target at which the carrot is dropped
+    pub fn set_close_dist(&mut self, close_dist: f64) {
+        self.close_dist = close_dist;
+    }
+
+    /// Settled once both the distance to the target and the heading error in radians
+    /// have settled.
+    pub fn set_settle(&mut self, dist: Settle, heading: Settle) {
+        self.dist_settle = dist;
+        self.heading_settle = heading;
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Drives until settled or timed out, then brakes.
+    ///
+    /// Returns `true` if the robot settled at the target.
+    pub async fn run<M: MotorGroup, L: Localiser>(
+        &mut self,
+        chassis: &Mutex<TankChassis<M>>,
+        localiser: &mut L,
+    ) -> bool {
+        self.linear.reset();
+        self.angular.reset();
+        self.dist_settle.reset();
+        self.heading_settle.reset();
+
+        let start = Instant::now();
+        let mut prev_time = start;
+        let mut settled = false;
+
+        while start.elapsed() < self.timeout {
+            let time_start = Instant::now();
+            let dt = time_start.duration_since(prev_time).as_secs_f64();
+            prev_time = time_start;
+
+            localiser.update().await;
+            let pose = localiser.pose();
+            let heading = pose.h.as_rad(AngleSystem::Cartesian);
+            let target_heading = self.target.h.as_rad(AngleSystem::Cartesian);
+            // to the target, in mm
+            let to_x = (self.target.x - pose.x).as_mm();
+            let to_y = (self.target.y - pose.y).as_mm();
+
+            let dist = to_x.hypot(to_y);
+            let heading_error = self.target.h.diff(pose.h);
+
+            // both detectors update every loop, so neither misses time
+            let dist_settled = self.dist_settle.update(dist, dt);
+            let heading_settled = self.heading_settle.update(heading_error, dt);
+            if dist_settled && heading_settled {
+                settled = true;
+                break;
+            }
+
+            let (linear_error, angular_error) = if dist < self.close_dist {
+                // only correct heading, drive along it to the target
+                let along = to_x * heading.cos() + to_y * heading.sin();
+                (along, heading_error)
+            } else {
+                let (sin, cos) = target_heading.sin_cos();
+                // from the robot to the carrot
+                let dx = to_x - self.lead * dist * cos;
+                let dy = to_y - self.lead * dist * sin;
+                let angle_error = Heading::new(dy.atan2(dx)).diff(pose.h);
+                // slow down while facing away from the carrot
+                (dx.hypot(dy) * angle_error.cos(), angle_error)
+            };
+
+            let throttle = self
+                .linear
+                .update(linear_error, dt)
+                .clamp(-self.max_speed, self.max_speed);
+            let steer = self
+                .angular
+                .update(angular_error, dt)
+                .clamp(-self.max_speed, self.max_speed);
+            chassis
+                .lock()
+                .await
+                .move_arcade(throttle as f32, steer as f32);
+
+            sleep_until(time_start + UPDATE_PERIOD).await;
+        }
+
+        chassis.lock().await.brake(BrakeMode::Brake);
+        settled
+    }
+}
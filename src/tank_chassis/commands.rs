@@ -0,0 +1,149 @@
+use core::{f64::consts, time::Duration};
+
+use crate::{
+    float::Float,
+    hal::{BrakeMode, MotorGroup},
+    localisation::{Heading, Localiser},
+    pid::{Pid, Settle},
+    rt::{sleep_until, Instant, Mutex},
+    tank_chassis::TankChassis,
+};
+
+const UPDATE_PERIOD: Duration = Duration::from_millis(10);
+
+/// Feedback and exit conditions for a chassis command
+pub struct CommandSettings {
+    /// output is a fraction of the maximum voltage
+    pub pid: Pid,
+    pub settle: Settle,
+    pub timeout: Duration,
+}
+
+impl CommandSettings {
+    pub fn new(pid: Pid, settle: Settle, timeout: Duration) -> Self {
+        CommandSettings {
+            pid,
+            settle,
+            timeout,
+        }
+    }
+}
+
+pub enum TurnDirection {
+    Shortest,
+    Clockwise,
+    Counterclockwise,
+}
+
+/// Closed loop commands using a localiser for feedback.
+///
+/// Implemented on the mutex rather than the chassis, since localisers that read
+/// the drive encoders need to lock the chassis while a command runs. Each command
+/// brakes when done and returns `true` if it settled before timing out.
+pub trait ChassisCommands {
+    /// Errors are in radians. `direction` is only forced until the robot is within a
+    /// quarter turn of the target, to avoid spinning again after an overshoot.
+    async fn turn_to_heading<L: Localiser>(
+        &self,
+        localiser: &mut L,
+        target: Heading,
+        direction: TurnDirection,
+        settings: &mut CommandSettings,
+    ) -> bool;
+
+    /// Drives straight along the starting heading, negative to reverse.
+    async fn drive_distance<L: Localiser>(
+        &self,
+        localiser: &mut L,
+        distance: f64,
+        settings: &mut CommandSettings,
+    ) -> bool;
+}
+
+impl<M: MotorGroup> ChassisCommands for Mutex<TankChassis<M>> {
+    async fn turn_to_heading<L: Localiser>(
+        &self,
+        localiser: &mut L,
+        target: Heading,
+        direction: TurnDirection,
+        settings: &mut CommandSettings,
+    ) -> bool {
+        settings.pid.reset();
+        settings.settle.reset();
+        let mut forced = !matches!(direction, TurnDirection::Shortest);
+
+        let start = Instant::now();
+        let mut prev_time = start;
+        let mut settled = false;
+        while start.elapsed() < settings.timeout {
+            let time_start = Instant::now();
+            let dt = time_start.duration_since(prev_time).as_secs_f64();
+            prev_time = time_start;
+
+            localiser.update().await;
+            // counterclockwise positive, in (-pi, pi]
+            let mut error = target.diff(localiser.pose().h);
+            if forced {
+                match direction {
+                    TurnDirection::Clockwise if error > 0.0 => error -= consts::TAU,
+                    TurnDirection::Counterclockwise if error < 0.0 => error += consts::TAU,
+                    _ => {}
+                }
+                // the way round is now also the shortest
+                forced = Float::abs(error) >= consts::FRAC_PI_2;
+            }
+
+            if settings.settle.update(error, dt) {
+                settled = true;
+                break;
+            }
+            let steer = settings.pid.update(error, dt);
+            self.lock().await.move_arcade(0.0, steer as f32);
+
+            sleep_until(time_start + UPDATE_PERIOD).await;
+        }
+
+        self.lock().await.brake(BrakeMode::Brake);
+        settled
+    }
+
+    async fn drive_distance<L: Localiser>(
+        &self,
+        localiser: &mut L,
+        distance: f64,
+        settings: &mut CommandSettings,
+    ) -> bool {
+        settings.pid.reset();
+        settings.settle.reset();
+
+        localiser.update().await;
+        let origin = localiser.pose();
+
+        let start = Instant::now();
+        let mut prev_time = start;
+        let mut settled = false;
+        while start.elapsed() < settings.timeout {
+            let time_start = Instant::now();
+            let dt = time_start.duration_since(prev_time).as_secs_f64();
+            prev_time = time_start;
+
+            localiser.update().await;
+            let pose = localiser.pose();
+            // progress along the starting heading
+            let travelled = origin.inverse_transform_point((pose.x, pose.y)).0.as_mm();
+            let error = distance - travelled;
+
+            if settings.settle.update(error, dt) {
+                settled = true;
+                break;
+            }
+            let throttle = settings.pid.update(error, dt);
+            self.lock().await.move_arcade(throttle as f32, 0.0);
+
+            sleep_until(time_start + UPDATE_PERIOD).await;
+        }
+
+        self.lock().await.brake(BrakeMode::Brake);
+        settled
+    }
+}
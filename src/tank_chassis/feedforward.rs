@@ -0,0 +1,28 @@
+use crate::float::Float;
+
+/// Voltage needed to hold a velocity and acceleration.
+///
+/// `ks` overcomes static friction, `kv` and `ka` are volts per unit of velocity
+/// and acceleration. [`TankChassis::move_velocity_ff`](super::TankChassis::move_velocity_ff)
+/// uses mm/s and mm/s².
+#[derive(Copy, Clone)]
+pub struct Feedforward {
+    pub ks: f64,
+    pub kv: f64,
+    pub ka: f64,
+}
+
+impl Feedforward {
+    pub fn new(ks: f64, kv: f64, ka: f64) -> Self {
+        Feedforward { ks, kv, ka }
+    }
+
+    pub fn calculate(&self, vel: f64, accel: f64) -> f64 {
+        let static_friction = if vel == 0.0 {
+            0.0
+        } else {
+            self.ks * Float::signum(vel)
+        };
+        static_friction + self.kv * vel + self.ka * accel
+    }
+}
@@ -0,0 +1,62 @@
+//! Differential drive kinematics.
+//!
+//! Wheel velocities are along the ground, angular velocity is counterclockwise
+//! positive.
+
+use crate::{
+    tank_chassis::model::TankVelocities,
+    units::{AngularVelocity, Length, LinearVelocity},
+};
+
+/// Linear and angular velocity of the tracking centre
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Twist {
+    pub linear: LinearVelocity,
+    pub angular: AngularVelocity,
+}
+
+impl Twist {
+    pub fn new(linear: LinearVelocity, angular: AngularVelocity) -> Self {
+        Twist { linear, angular }
+    }
+
+    /// Per mm, infinite when turning in place, NaN when stopped
+    pub fn curvature(&self) -> f64 {
+        self.angular.as_rad_per_sec() / self.linear.as_mm_per_sec()
+    }
+}
+
+/// Inverse kinematics, the wheel velocities producing `twist`.
+pub fn wheel_vels(twist: Twist, track_width: Length) -> TankVelocities {
+    // each wheel is half a track width from the centre of the turn
+    let turn =
+        LinearVelocity::from_mm_per_sec(twist.angular.as_rad_per_sec() * track_width.as_mm() / 2.0);
+    TankVelocities {
+        left: twist.linear - turn,
+        right: twist.linear + turn,
+    }
+}
+
+/// Forward kinematics, the twist produced by `vels`.
+pub fn twist(vels: TankVelocities, track_width: Length) -> Twist {
+    Twist {
+        linear: (vels.left + vels.right) / 2.0,
+        angular: AngularVelocity::from_rad_per_sec(
+            (vels.right - vels.left).as_mm_per_sec() / track_width.as_mm(),
+        ),
+    }
+}
+
+/// Scales both wheels down so neither exceeds `max`, keeping their ratio and so the
+/// curvature.
+pub fn desaturate(vels: TankVelocities, max: LinearVelocity) -> TankVelocities {
+    let fastest = vels.left.abs().max(vels.right.abs());
+    if fastest <= max {
+        return vels;
+    }
+    let scale = max / fastest;
+    TankVelocities {
+        left: vels.left * scale,
+        right: vels.right * scale,
+    }
+}
@@ -1,42 +1,134 @@
+pub mod boomerang;
+pub mod commands;
+pub mod feedforward;
+pub mod kinematics;
 pub mod model;
 pub mod mp;
+pub mod path;
+pub mod pure_pursuit;
+pub mod ramsete;
 
-use alloc::vec::Vec;
+use core::time::Duration;
 
-use vexide::prelude::*;
+use crate::{
+    float::Float,
+    hal::{BrakeMode, MotorGroup, MAX_VOLTAGE},
+    pid::Pid,
+    rt::Instant,
+    tank_chassis::feedforward::Feedforward,
+    units::{Angle, Length, LinearAcceleration, LinearVelocity},
+};
 
-pub struct TankChassis {
-    left: Vec<Motor>,
-    right: Vec<Motor>,
+// velocity pid is restarted if updates are further apart than this
+const VELOCITY_PID_GAP: Duration = Duration::from_millis(100);
+
+// scales fractions of the maximum down so neither exceeds it, keeping their ratio
+fn desaturate(left: f64, right: f64) -> (f64, f64) {
+    let fastest = Float::abs(left).max(Float::abs(right));
+    if fastest <= 1.0 {
+        return (left, right);
+    }
+    (left / fastest, right / fastest)
 }
 
-impl TankChassis {
-    pub fn new(l1: Motor, l2: Motor, lt: Motor, r1: Motor, r2: Motor, rt: Motor) -> Self {
-        let vec_left: Vec<Motor> = vec![l1, l2, lt];
-        let vec_right: Vec<Motor> = vec![r1, r2, rt];
+pub struct TankChassis<M: MotorGroup> {
+    left: M,
+    right: M,
+
+    left_ff: Feedforward,
+    right_ff: Feedforward,
+    velocity_pid: Option<(Pid, Pid)>,
+    dist_per_rev: Length,
+    prev_velocity_update: Option<Instant>,
+}
+
+impl<M: MotorGroup> TankChassis<M> {
+    pub fn new(left: M, right: M) -> Self {
         TankChassis {
-            left: vec_left,
-            right: vec_right,
+            left,
+            right,
+            left_ff: Feedforward::new(0.0, 0.0, 0.0),
+            right_ff: Feedforward::new(0.0, 0.0, 0.0),
+            velocity_pid: None,
+            dist_per_rev: Length::ZERO,
+            prev_velocity_update: None,
         }
     }
 
-    pub fn move_tank(&mut self, mut left: f32, mut right: f32) {
-        if left.abs() > 1.0 || right.abs() > 1.0 {
-            let mut max = left.abs();
-            if right.abs() > left.abs() {
-                max = right.abs();
+    pub fn set_feedforward(&mut self, left: Feedforward, right: Feedforward) {
+        self.left_ff = left;
+        self.right_ff = right;
+    }
+
+    /// Feedback on measured wheel velocity, added to the feedforward. Outputs volts.
+    ///
+    /// Needs [`TankChassis::set_dist_per_rev`] to measure wheel velocity.
+    pub fn set_velocity_pid(&mut self, pid: Option<(Pid, Pid)>) {
+        self.velocity_pid = pid;
+    }
+
+    /// distance a wheel travels per motor revolution, including gearing
+    pub fn set_dist_per_rev(&mut self, dist_per_rev: Length) {
+        self.dist_per_rev = dist_per_rev;
+    }
+
+    /// Drives each side at a wheel velocity and acceleration.
+    ///
+    /// The feedforward gains and velocity pid work in mm/s and mm/s².
+    pub fn move_velocity_ff(
+        &mut self,
+        left_vel: LinearVelocity,
+        right_vel: LinearVelocity,
+        left_accel: LinearAcceleration,
+        right_accel: LinearAcceleration,
+    ) {
+        let mut left = self
+            .left_ff
+            .calculate(left_vel.as_mm_per_sec(), left_accel.as_mm_per_sec2());
+        let mut right = self
+            .right_ff
+            .calculate(right_vel.as_mm_per_sec(), right_accel.as_mm_per_sec2());
+
+        let now = Instant::now();
+        let dt = self
+            .prev_velocity_update
+            .replace(now)
+            .map(|prev| now.duration_since(prev));
+        let measured = (self.left_vel(), self.right_vel());
+        if let Some((left_pid, right_pid)) = self.velocity_pid.as_mut() {
+            if dt.is_none_or(|dt| dt > VELOCITY_PID_GAP) {
+                left_pid.reset();
+                right_pid.reset();
             }
-            left /= max;
-            right /= max;
-        }
-        left *= 12.0;
-        right *= 12.0;
-        for motor in self.left.iter_mut() {
-            motor.set_voltage(left.into()).ok();
-        }
-        for motor in self.right.iter_mut() {
-            motor.set_voltage(right.into()).ok();
+            let dt = dt.map_or(0.0, |dt| dt.as_secs_f64());
+            left +=
+                left_pid.update_measured(left_vel.as_mm_per_sec(), measured.0.as_mm_per_sec(), dt);
+            right += right_pid.update_measured(
+                right_vel.as_mm_per_sec(),
+                measured.1.as_mm_per_sec(),
+                dt,
+            );
         }
+
+        self.left.set_voltage(left.clamp(-MAX_VOLTAGE, MAX_VOLTAGE));
+        self.right
+            .set_voltage(right.clamp(-MAX_VOLTAGE, MAX_VOLTAGE));
+    }
+
+    /// powers are fractions of the maximum voltage
+    pub fn move_tank(&mut self, left: f32, right: f32) {
+        let (left, right) = desaturate(left.into(), right.into());
+        self.left.set_voltage(left * MAX_VOLTAGE);
+        self.right.set_voltage(right * MAX_VOLTAGE);
+    }
+
+    /// velocities are fractions of the motors' maximum speed
+    pub fn move_velocity(&mut self, left: f64, right: f64) {
+        let (left, right) = desaturate(left, right);
+        let left_rpm = left * self.left.max_rpm();
+        let right_rpm = right * self.right.max_rpm();
+        self.left.set_velocity(left_rpm);
+        self.right.set_velocity(right_rpm);
     }
 
     pub fn move_arcade(&mut self, throttle: f32, steer: f32) {
@@ -44,34 +136,27 @@ impl TankChassis {
     }
 
     pub fn brake(&mut self, mode: BrakeMode) {
-        for motor in self.left.iter_mut() {
-            motor.brake(mode).ok();
-        }
-        for motor in self.right.iter_mut() {
-            motor.brake(mode).ok();
-        }
+        self.left.brake(mode);
+        self.right.brake(mode);
     }
 
-    pub fn left_deg(&self) -> f64 {
-        let mut sum: f64 = 0.0;
-        for motor in self.left.iter() {
-            sum += motor
-                .position()
-                .unwrap_or(Position::from_degrees(0.0))
-                .as_degrees();
-        }
+    /// how far the left motors have turned
+    pub fn left_angle(&self) -> Angle {
+        Angle::from_deg(self.left.position())
+    }
 
-        sum / self.left.len() as f64
+    /// how far the right motors have turned
+    pub fn right_angle(&self) -> Angle {
+        Angle::from_deg(self.right.position())
     }
 
-    pub fn right_deg(&self) -> f64 {
-        let mut sum: f64 = 0.0;
-        for motor in self.right.iter() {
-            sum += motor
-                .position()
-                .unwrap_or(Position::from_degrees(0.0))
-                .as_degrees();
-        }
-        sum / self.right.len() as f64
+    /// measured wheel velocity of the left side
+    pub fn left_vel(&self) -> LinearVelocity {
+        LinearVelocity::from_mm_per_sec(self.left.velocity() / 60.0 * self.dist_per_rev.as_mm())
+    }
+
+    /// measured wheel velocity of the right side
+    pub fn right_vel(&self) -> LinearVelocity {
+        LinearVelocity::from_mm_per_sec(self.right.velocity() / 60.0 * self.dist_per_rev.as_mm())
     }
 }
@@ -1,46 +1,195 @@
-use crate::tank_chassis::mp::TankConstraints;
+use crate::{
+    float::Float,
+    tank_chassis::{
+        kinematics::{self, Twist},
+        mp::TankConstraints,
+    },
+    units::{
+        AngularAcceleration, AngularVelocity, Length, LinearAcceleration, LinearJerk,
+        LinearVelocity,
+    },
+};
+
+const GRAVITY: LinearAcceleration = LinearAcceleration::from_mm_per_sec2(9806.65);
 
 pub struct TankModel {
     mass: f64,
     lateral_mu: f64,
-    track_width: f64,
-    lin_speed: f64,
+    track_width: Length,
+    lin_speed: LinearVelocity,
     force: f64,
+    gravity: LinearAcceleration,
+}
+
+/// Builds a [`TankModel`], see [`TankModel::builder`].
+pub struct TankModelBuilder {
+    model: TankModel,
+}
+
+impl TankModelBuilder {
+    pub fn mass(mut self, mass: f64) -> Self {
+        self.model.mass = mass;
+        self
+    }
+
+    /// coefficient of friction resisting sideways sliding
+    pub fn lateral_mu(mut self, lateral_mu: f64) -> Self {
+        self.model.lateral_mu = lateral_mu;
+        self
+    }
+
+    /// combined driving force of both sides at stall, in mass times mm/s^2
+    pub fn force(mut self, force: f64) -> Self {
+        self.model.force = force;
+        self
+    }
+
+    pub fn gravity(mut self, gravity: LinearAcceleration) -> Self {
+        self.model.gravity = gravity;
+        self
+    }
+
+    pub fn build(self) -> TankModel {
+        self.model
+    }
 }
 
+#[derive(Copy, Clone)]
 pub struct TankVelocities {
-    pub left: f64,
-    pub right: f64,
+    pub left: LinearVelocity,
+    pub right: LinearVelocity,
 }
 
 impl TankModel {
-    fn wheel_vels(&self, linear_velocity: f64, curvature: f64) -> TankVelocities {
+    pub fn new(
+        mass: f64,
+        lateral_mu: f64,
+        track_width: Length,
+        lin_speed: LinearVelocity,
+        force: f64,
+    ) -> Self {
+        TankModel {
+            mass,
+            lateral_mu,
+            track_width,
+            lin_speed,
+            force,
+            gravity: GRAVITY,
+        }
+    }
+
+    /// Model with only the track width and side speed limited.
+    ///
+    /// Mass defaults to 1 and the other limits to infinity until set.
+    pub fn builder(track_width: Length, lin_speed: LinearVelocity) -> TankModelBuilder {
+        TankModelBuilder {
+            model: TankModel::new(1.0, f64::INFINITY, track_width, lin_speed, f64::INFINITY),
+        }
+    }
+
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// combined driving force of both sides at stall, in mass times mm/s^2
+    pub fn force(&self) -> f64 {
+        self.force
+    }
+
+    /// coefficient of friction resisting sideways sliding
+    pub fn lateral_mu(&self) -> f64 {
+        self.lateral_mu
+    }
+
+    pub fn track_width(&self) -> Length {
+        self.track_width
+    }
+
+    /// maximum linear speed of each side
+    pub fn lin_speed(&self) -> LinearVelocity {
+        self.lin_speed
+    }
+
+    /// maximum angular velocity, turning in place
+    pub fn max_ang_vel(&self) -> AngularVelocity {
+        AngularVelocity::from_rad_per_sec(
+            2.0 * self.lin_speed.as_mm_per_sec() / self.track_width.as_mm(),
+        )
+    }
+
+    /// maximum linear acceleration of each side
+    pub fn max_accel(&self) -> LinearAcceleration {
+        LinearAcceleration::from_mm_per_sec2(self.force / self.mass)
+    }
+
+    /// maximum angular acceleration, turning in place
+    pub fn max_ang_accel(&self) -> AngularAcceleration {
+        AngularAcceleration::from_rad_per_sec2(
+            2.0 * self.max_accel().as_mm_per_sec2() / self.track_width.as_mm(),
+        )
+    }
+
+    /// Wheel velocities to follow `curvature`, per mm, at `linear_velocity`.
+    ///
+    /// Infinite curvature turns in place, with `linear_velocity` as the speed of each
+    /// wheel.
+    pub fn wheel_vels(&self, linear_velocity: LinearVelocity, curvature: f64) -> TankVelocities {
+        if curvature.is_infinite() {
+            let right = linear_velocity * curvature.signum();
+            return TankVelocities {
+                left: -right,
+                right,
+            };
+        }
+
         // turning left is positive curvature
-        let angular_velocity = linear_velocity * curvature;
+        let angular_velocity =
+            AngularVelocity::from_rad_per_sec(linear_velocity.as_mm_per_sec() * curvature);
+        self.twist_to_wheels(Twist::new(linear_velocity, angular_velocity))
+    }
 
-        let left_r = 1.0 / curvature - self.track_width / 2.0;
-        let right_r = 1.0 / curvature + self.track_width / 2.0;
+    pub fn twist_to_wheels(&self, twist: Twist) -> TankVelocities {
+        kinematics::wheel_vels(twist, self.track_width)
+    }
 
-        let left = angular_velocity * left_r;
-        let right = angular_velocity * right_r;
+    pub fn wheels_to_twist(&self, vels: TankVelocities) -> Twist {
+        kinematics::twist(vels, self.track_width)
+    }
 
-        TankVelocities { left, right }
+    /// Slows both sides so neither exceeds `lin_speed`, keeping the curvature.
+    pub fn desaturate(&self, vels: TankVelocities) -> TankVelocities {
+        kinematics::desaturate(vels, self.lin_speed)
     }
 
-    fn constraints(&self, curvature: f64) -> TankConstraints {
-        let mut linear_velocity = self.lin_speed;
-        if curvature > 0.0 {
-            // left turn, right > left
-            let right_r = 1.0 / curvature + self.track_width / 2.0;
-            linear_velocity = self.lin_speed / (curvature * right_r);
-        } else if curvature < 0.0 {
-            // right turn, left > right
-            let left_r = 1.0 / curvature - self.track_width / 2.0;
-            linear_velocity = self.lin_speed / (curvature * left_r);
+    /// Limits on linear motion along a path of constant `curvature`, per mm.
+    ///
+    /// The outer wheel travels `1 + |curvature| * track_width / 2` times as fast as the
+    /// centre, so both its speed and acceleration cap the centre's. Speed is also capped
+    /// where the centripetal force would exceed lateral friction, which a straight line
+    /// has none of. Turning in place has no linear motion, so both limits are zero.
+    pub fn constraints(&self, curvature: f64) -> TankConstraints {
+        if curvature.is_infinite() {
+            return TankConstraints {
+                max_vel: LinearVelocity::ZERO,
+                max_accel: LinearAcceleration::ZERO,
+                max_jerk: LinearJerk::INFINITY,
+            };
         }
+        let outer_scale = 1.0 + curvature.abs() * self.track_width.as_mm() / 2.0;
+
+        // m v^2 |curvature| <= mu m g
+        let friction_vel = if curvature == 0.0 {
+            LinearVelocity::INFINITY
+        } else {
+            let friction_force = self.lateral_mu * self.mass * self.gravity.as_mm_per_sec2();
+            LinearVelocity::from_mm_per_sec((friction_force / (self.mass * curvature.abs())).sqrt())
+        };
+
         TankConstraints {
-            max_vel: linear_velocity,
-            max_accel: self.force / self.mass,
+            max_vel: (self.lin_speed / outer_scale).min(friction_vel),
+            max_accel: self.max_accel() / outer_scale,
+            // jerk is not limited by the physical model
+            max_jerk: LinearJerk::INFINITY,
         }
     }
 }
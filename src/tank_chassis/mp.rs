@@ -1,4 +1,316 @@
+use alloc::{vec, vec::Vec};
+use core::{iter, time::Duration};
+
+use crate::{
+    float::Float,
+    units::{Length, LinearAcceleration, LinearJerk, LinearVelocity},
+};
+
+/// Limits on a profile's motion.
+///
+/// Infinite limits are never reached. A profile with a limit that isn't positive
+/// never arrives, holding its start forever.
 pub struct TankConstraints {
-  pub max_vel: f64,
-  pub max_accel: f64,
-}
\ No newline at end of file
+    pub max_vel: LinearVelocity,
+    pub max_accel: LinearAcceleration,
+    /// only used by jerk limited profiles
+    pub max_jerk: LinearJerk,
+}
+
+// profiles that never arrive last forever
+fn secs(t: f64) -> Duration {
+    Duration::try_from_secs_f64(t).unwrap_or(Duration::MAX)
+}
+
+/// State of a 1d motion profile at time `t` since the start of the profile
+#[derive(Copy, Clone)]
+pub struct ProfileState {
+    pub t: Duration,
+    pub pos: Length,
+    pub vel: LinearVelocity,
+    pub accel: LinearAcceleration,
+}
+
+impl ProfileState {
+    // from seconds and mm, which the profiles are calculated in
+    fn new(t: f64, pos: f64, vel: f64, accel: f64) -> Self {
+        ProfileState {
+            t: secs(t),
+            pos: Length::from_mm(pos),
+            vel: LinearVelocity::from_mm_per_sec(vel),
+            accel: LinearAcceleration::from_mm_per_sec2(accel),
+        }
+    }
+}
+
+pub trait MotionProfile {
+    fn duration(&self) -> Duration;
+
+    /// times outside of the profile are clamped to its end
+    fn sample(&self, t: Duration) -> ProfileState;
+
+    /// Samples every `dt`, always ending with the final state. A profile that never
+    /// arrives only has its start, and a zero `dt` only gives the final state.
+    fn samples(&self, dt: Duration) -> Vec<ProfileState> {
+        let duration = self.duration();
+        if duration == Duration::MAX {
+            return vec![self.sample(Duration::ZERO)];
+        }
+        if dt.is_zero() {
+            return vec![self.sample(duration)];
+        }
+        let mut states: Vec<ProfileState> =
+            iter::successors(Some(Duration::ZERO), |t| t.checked_add(dt))
+                .take_while(|&t| t < duration)
+                .map(|t| self.sample(t))
+                .collect();
+        states.push(self.sample(duration));
+        states
+    }
+}
+
+/// Acceleration limited profile from rest to rest.
+///
+/// When the distance is too short to reach the maximum velocity the cruise phase
+/// is dropped, giving a triangular velocity curve.
+pub struct TrapezoidProfile {
+    direction: f64,
+    dist: f64,
+    accel: f64,
+    peak_vel: f64,
+    t_accel: f64,
+    t_cruise: f64,
+}
+
+impl TrapezoidProfile {
+    pub fn new(distance: Length, constraints: &TankConstraints) -> Self {
+        let distance = distance.as_mm();
+        let dist = distance.abs();
+        let accel = constraints.max_accel.as_mm_per_sec2();
+        let max_vel = constraints.max_vel.as_mm_per_sec();
+        let mut profile = TrapezoidProfile {
+            direction: distance.signum(),
+            dist,
+            accel: 0.0,
+            peak_vel: 0.0,
+            t_accel: 0.0,
+            t_cruise: 0.0,
+        };
+        if dist == 0.0 || (max_vel.is_infinite() && accel.is_infinite()) {
+            // there already, or there instantly
+            return profile;
+        }
+        if !(max_vel > 0.0 && accel > 0.0) {
+            profile.t_cruise = f64::INFINITY;
+            return profile;
+        }
+
+        let mut peak_vel = max_vel;
+        let accel_dist = peak_vel * peak_vel / (2.0 * accel);
+        if 2.0 * accel_dist > dist {
+            // triangular, accelerate for half the distance
+            peak_vel = (dist * accel).sqrt();
+        } else {
+            profile.t_cruise = (dist - 2.0 * accel_dist) / peak_vel;
+        }
+        profile.accel = accel;
+        profile.peak_vel = peak_vel;
+        profile.t_accel = peak_vel / accel;
+        profile
+    }
+}
+
+impl TrapezoidProfile {
+    fn total_time(&self) -> f64 {
+        2.0 * self.t_accel + self.t_cruise
+    }
+}
+
+impl MotionProfile for TrapezoidProfile {
+    fn duration(&self) -> Duration {
+        secs(self.total_time())
+    }
+
+    fn sample(&self, t: Duration) -> ProfileState {
+        let t = t.as_secs_f64().min(self.total_time());
+        let accel_dist = 0.5 * self.peak_vel * self.t_accel;
+        let t_decel = self.t_accel + self.t_cruise;
+
+        let (pos, vel, accel) = if t < self.t_accel {
+            (0.5 * self.accel * t * t, self.accel * t, self.accel)
+        } else if t < t_decel {
+            (
+                accel_dist + self.peak_vel * (t - self.t_accel),
+                self.peak_vel,
+                0.0,
+            )
+        } else if t < self.total_time() {
+            let dt = t - t_decel;
+            (
+                accel_dist + self.peak_vel * self.t_cruise + self.peak_vel * dt
+                    - 0.5 * self.accel * dt * dt,
+                self.peak_vel - self.accel * dt,
+                -self.accel,
+            )
+        } else {
+            (self.dist, 0.0, 0.0)
+        };
+
+        ProfileState::new(
+            t,
+            self.direction * pos,
+            self.direction * vel,
+            self.direction * accel,
+        )
+    }
+}
+
+/// Jerk limited profile from rest to rest.
+///
+/// Made of seven phases: jerk up, constant acceleration, jerk down, cruise, and the
+/// mirror image to decelerate. Phases that are not needed to meet the distance have
+/// zero duration. An infinite jerk limit gives the same motion as a trapezoid.
+pub struct SCurveProfile {
+    direction: f64,
+    dist: f64,
+    jerk: f64,
+    peak_accel: f64,
+    peak_vel: f64,
+    t_jerk: f64,
+    t_accel: f64,
+    t_cruise: f64,
+}
+
+impl SCurveProfile {
+    pub fn new(distance: Length, constraints: &TankConstraints) -> Self {
+        let distance = distance.as_mm();
+        let dist = distance.abs();
+        let max_vel = constraints.max_vel.as_mm_per_sec();
+        let accel = constraints.max_accel.as_mm_per_sec2();
+        let jerk = constraints.max_jerk.as_mm_per_sec3();
+        let mut profile = SCurveProfile {
+            direction: distance.signum(),
+            dist,
+            jerk: 0.0,
+            peak_accel: 0.0,
+            peak_vel: 0.0,
+            t_jerk: 0.0,
+            t_accel: 0.0,
+            t_cruise: 0.0,
+        };
+        if dist == 0.0 || (max_vel.is_infinite() && accel.is_infinite() && jerk.is_infinite()) {
+            // there already, or there instantly
+            return profile;
+        }
+        if !(max_vel > 0.0 && accel > 0.0 && jerk > 0.0) {
+            profile.t_cruise = f64::INFINITY;
+            return profile;
+        }
+
+        // velocity at which the acceleration limit is just reached
+        let accel_limited_vel = accel * accel / jerk;
+
+        let mut peak_vel = max_vel;
+        let (t_jerk, t_accel) = Self::accel_phase(peak_vel, accel, jerk);
+        let accel_dist = peak_vel * (2.0 * t_jerk + t_accel) / 2.0;
+        if 2.0 * accel_dist <= dist {
+            profile.t_cruise = (dist - 2.0 * accel_dist) / peak_vel;
+        } else {
+            // peak velocity where the acceleration and deceleration phases meet,
+            // first assuming the acceleration limit is reached
+            peak_vel = (-accel_limited_vel
+                + (accel_limited_vel * accel_limited_vel + 4.0 * accel * dist).sqrt())
+                / 2.0;
+            // NaN when the acceleration limit is infinite, so never reached
+            if peak_vel.is_nan() || peak_vel < accel_limited_vel {
+                peak_vel = (dist * dist / 4.0 * jerk).cbrt();
+            }
+        }
+
+        let (t_jerk, t_accel) = Self::accel_phase(peak_vel, accel, jerk);
+        profile.jerk = jerk;
+        profile.peak_accel = if t_jerk > 0.0 { jerk * t_jerk } else { accel };
+        profile.peak_vel = peak_vel;
+        profile.t_jerk = t_jerk;
+        profile.t_accel = t_accel;
+        profile
+    }
+
+    /// durations of each jerk phase and the constant acceleration phase to reach `vel`
+    fn accel_phase(vel: f64, accel: f64, jerk: f64) -> (f64, f64) {
+        if accel.is_infinite() {
+            // the acceleration limit is never reached
+            if jerk.is_infinite() {
+                (0.0, 0.0)
+            } else {
+                ((vel / jerk).sqrt(), 0.0)
+            }
+        } else if vel * jerk >= accel * accel {
+            (accel / jerk, vel / accel - accel / jerk)
+        } else {
+            ((vel / jerk).sqrt(), 0.0)
+        }
+    }
+
+    /// (duration, starting acceleration, jerk) of each phase
+    fn phases(&self) -> [(f64, f64, f64); 7] {
+        let (j, a) = (self.jerk, self.peak_accel);
+        [
+            (self.t_jerk, 0.0, j),
+            (self.t_accel, a, 0.0),
+            (self.t_jerk, a, -j),
+            (self.t_cruise, 0.0, 0.0),
+            (self.t_jerk, 0.0, -j),
+            (self.t_accel, -a, 0.0),
+            (self.t_jerk, -a, j),
+        ]
+    }
+
+    fn total_time(&self) -> f64 {
+        4.0 * self.t_jerk + 2.0 * self.t_accel + self.t_cruise
+    }
+}
+
+impl MotionProfile for SCurveProfile {
+    fn duration(&self) -> Duration {
+        secs(self.total_time())
+    }
+
+    fn sample(&self, t: Duration) -> ProfileState {
+        let t = t.as_secs_f64().min(self.total_time());
+
+        let mut start = 0.0;
+        let mut pos = 0.0;
+        let mut vel = 0.0;
+        let mut accel = 0.0;
+        for (i, (duration, phase_accel, jerk)) in self.phases().into_iter().enumerate() {
+            if duration <= 0.0 {
+                continue;
+            }
+            // set rather than integrated, as infinite acceleration jumps to it
+            if i == 3 {
+                vel = self.peak_vel;
+            }
+            let dt = (t - start).min(duration);
+            pos += vel * dt + phase_accel * dt * dt / 2.0 + jerk * dt * dt * dt / 6.0;
+            vel += phase_accel * dt + jerk * dt * dt / 2.0;
+            accel = phase_accel + jerk * dt;
+            if t < start + duration {
+                break;
+            }
+            start += duration;
+        }
+        if t >= self.total_time() {
+            pos = self.dist;
+            vel = 0.0;
+            accel = 0.0;
+        }
+
+        ProfileState::new(
+            t,
+            self.direction * pos,
+            self.direction * vel,
+            self.direction * accel,
+        )
+    }
+}
@@ -0,0 +1,347 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{
+    float::Float,
+    localisation::{AngleSystem, Heading, Pose},
+    tank_chassis::{
+        model::{TankModel, TankVelocities},
+        mp::{MotionProfile, ProfileState},
+    },
+    units::{AngularVelocity, Length, LinearAcceleration, LinearVelocity},
+};
+
+#[derive(Copy, Clone)]
+pub struct PathPoint {
+    pub pose: Pose,
+    /// arc length from the start of the path
+    pub s: Length,
+    /// per mm, positive when turning left
+    pub curvature: f64,
+}
+
+pub struct Path {
+    points: Vec<PathPoint>,
+}
+
+impl Path {
+    /// Builds a path from densely sampled `(x, y)` points.
+    ///
+    /// Heading follows the direction of travel and curvature is estimated from
+    /// each point and its neighbours. Repeated points are skipped.
+    pub fn from_points(xy: &[(f64, f64)]) -> Self {
+        let mut unique: Vec<(f64, f64)> = Vec::with_capacity(xy.len());
+        for &p in xy {
+            if unique
+                .last()
+                .is_none_or(|q| (p.0 - q.0).hypot(p.1 - q.1) > 1e-9)
+            {
+                unique.push(p);
+            }
+        }
+
+        let n = unique.len();
+        let mut points: Vec<PathPoint> = Vec::with_capacity(n);
+        let mut s = 0.0;
+        for i in 0..n {
+            let (x, y) = unique[i];
+            if i > 0 {
+                let (px, py) = unique[i - 1];
+                s += (x - px).hypot(y - py);
+            }
+
+            // direction of the segment leaving the point, or entering the last point
+            let (ax, ay) = unique[i.min(n.saturating_sub(2))];
+            let (bx, by) = unique[(i + 1).min(n - 1)];
+            let h = Heading::from_rad((by - ay).atan2(bx - ax), AngleSystem::Cartesian);
+
+            let curvature = if i == 0 || i == n - 1 {
+                0.0
+            } else {
+                menger_curvature(unique[i - 1], unique[i], unique[i + 1])
+            };
+
+            points.push(PathPoint {
+                pose: Pose::new(Length::from_mm(x), Length::from_mm(y), h),
+                s: Length::from_mm(s),
+                curvature,
+            });
+        }
+
+        // ends take the curvature of their neighbours, unless the path turns back there
+        if n >= 3 {
+            if points[1].curvature.is_finite() {
+                points[0].curvature = points[1].curvature;
+            }
+            if points[n - 2].curvature.is_finite() {
+                points[n - 1].curvature = points[n - 2].curvature;
+            }
+        }
+
+        Path { points }
+    }
+
+    pub fn points(&self) -> &[PathPoint] {
+        &self.points
+    }
+
+    pub fn length(&self) -> Length {
+        self.points.last().map_or(Length::ZERO, |p| p.s)
+    }
+}
+
+/// Signed curvature of the circle through three points, infinite where the path
+/// doubles back on itself.
+fn menger_curvature(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    let cross = (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0);
+    let ab = (b.0 - a.0).hypot(b.1 - a.1);
+    let bc = (c.0 - b.0).hypot(c.1 - b.1);
+    let ca = (a.0 - c.0).hypot(a.1 - c.1);
+    if ca == 0.0 {
+        return f64::INFINITY;
+    }
+    2.0 * cross / (ab * bc * ca)
+}
+
+// longest step between profiled points, so there is room to accelerate between them
+const MAX_SPACING: f64 = 10.0;
+
+/// Velocity profile along a path, from rest to rest.
+///
+/// Each point is limited by the model's constraints at its curvature, then a forward
+/// pass limits acceleration and a backward pass limits deceleration. `pos` of each
+/// state is the arc length. Sparse paths are split into steps of at most 10 mm, and
+/// the robot stops wherever the path turns back on itself.
+pub struct PathProfile {
+    points: Vec<PathPoint>,
+    states: Vec<ProfileState>,
+}
+
+impl PathProfile {
+    pub fn new(path: &Path, model: &TankModel) -> Self {
+        let points = densify(path.points());
+        let n = points.len();
+        let constraints: Vec<_> = points
+            .iter()
+            .map(|p| model.constraints(p.curvature))
+            .collect();
+
+        // arc length, velocity and acceleration limit at each point, in mm and seconds
+        let s: Vec<f64> = points.iter().map(|p| p.s.as_mm()).collect();
+        let max_accel: Vec<f64> = constraints
+            .iter()
+            .map(|c| c.max_accel.as_mm_per_sec2())
+            .collect();
+        let mut vels: Vec<f64> = constraints
+            .iter()
+            .map(|c| c.max_vel.as_mm_per_sec())
+            .collect();
+        if n > 0 {
+            vels[0] = 0.0;
+            vels[n - 1] = 0.0;
+        }
+
+        // forward pass, v^2 = u^2 + 2as
+        for i in 1..n {
+            let ds = s[i] - s[i - 1];
+            let reachable = (vels[i - 1] * vels[i - 1] + 2.0 * max_accel[i - 1] * ds).sqrt();
+            vels[i] = vels[i].min(reachable);
+        }
+        // backward pass
+        for i in (0..n.saturating_sub(1)).rev() {
+            let ds = s[i + 1] - s[i];
+            let reachable = (vels[i + 1] * vels[i + 1] + 2.0 * max_accel[i + 1] * ds).sqrt();
+            vels[i] = vels[i].min(reachable);
+        }
+
+        let mut states: Vec<ProfileState> = Vec::with_capacity(n);
+        let mut t = 0.0;
+        for i in 0..n {
+            let accel = if i + 1 < n {
+                let ds = s[i + 1] - s[i];
+                (vels[i + 1] * vels[i + 1] - vels[i] * vels[i]) / (2.0 * ds)
+            } else {
+                0.0
+            };
+            states.push(ProfileState {
+                t: Duration::try_from_secs_f64(t).unwrap_or(Duration::MAX),
+                pos: points[i].s,
+                vel: LinearVelocity::from_mm_per_sec(vels[i]),
+                accel: LinearAcceleration::from_mm_per_sec2(accel),
+            });
+            if i + 1 < n {
+                let ds = s[i + 1] - s[i];
+                let avg_vel = (vels[i] + vels[i + 1]) / 2.0;
+                if avg_vel > 0.0 {
+                    t += ds / avg_vel;
+                } else {
+                    // leaving a point where the path turns back, which has no
+                    // acceleration limit of its own, from rest to rest
+                    let accel = max_accel[i].max(max_accel[i + 1]);
+                    t += 2.0 * (ds / accel).sqrt();
+                }
+            }
+        }
+
+        PathProfile { points, states }
+    }
+
+    pub fn points(&self) -> &[PathPoint] {
+        &self.points
+    }
+
+    pub fn states(&self) -> &[ProfileState] {
+        &self.states
+    }
+
+    /// left and right wheel velocity targets at each point
+    pub fn wheel_vels(&self, model: &TankModel) -> Vec<TankVelocities> {
+        self.points
+            .iter()
+            .zip(self.states.iter())
+            .map(|(p, state)| model.wheel_vels(state.vel, p.curvature))
+            .collect()
+    }
+
+    /// trajectory following the path at the profiled velocity
+    pub fn trajectory(&self) -> Trajectory {
+        Trajectory::new(
+            self.points
+                .iter()
+                .zip(self.states.iter())
+                .map(|(p, state)| TrajectoryState {
+                    t: state.t,
+                    pose: p.pose,
+                    lin_vel: state.vel,
+                    ang_vel: AngularVelocity::from_rad_per_sec(
+                        state.vel.as_mm_per_sec() * p.curvature,
+                    ),
+                })
+                .collect(),
+        )
+    }
+
+    /// index of the last state at or before time `t`
+    fn index_at(&self, t: Duration) -> usize {
+        self.states
+            .partition_point(|state| state.t <= t)
+            .saturating_sub(1)
+    }
+
+    /// point reached at time `t`, `None` for an empty path
+    pub fn point_at(&self, t: Duration) -> Option<PathPoint> {
+        self.points.get(self.index_at(t)).copied()
+    }
+}
+
+impl MotionProfile for PathProfile {
+    fn duration(&self) -> Duration {
+        self.states.last().map_or(Duration::ZERO, |state| state.t)
+    }
+
+    fn sample(&self, t: Duration) -> ProfileState {
+        let t = t.min(self.duration());
+        let Some(state) = self.states.get(self.index_at(t)) else {
+            return ProfileState {
+                t,
+                pos: Length::ZERO,
+                vel: LinearVelocity::ZERO,
+                accel: LinearAcceleration::ZERO,
+            };
+        };
+        // constant acceleration between points
+        let dt = t - state.t;
+        ProfileState {
+            t,
+            pos: state.pos + state.vel * dt + state.accel * dt * dt / 2.0,
+            vel: state.vel + state.accel * dt,
+            accel: state.accel,
+        }
+    }
+}
+
+// splits long segments, keeping the heading of the segment and blending curvature
+fn densify(path: &[PathPoint]) -> Vec<PathPoint> {
+    let mut points: Vec<PathPoint> = Vec::with_capacity(path.len());
+    for pair in path.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let steps = ((b.s - a.s).as_mm() / MAX_SPACING).ceil().max(1.0) as usize;
+        points.extend((0..steps).map(|i| {
+            let frac = i as f64 / steps as f64;
+            PathPoint {
+                pose: Pose::new(
+                    a.pose.x + (b.pose.x - a.pose.x) * frac,
+                    a.pose.y + (b.pose.y - a.pose.y) * frac,
+                    a.pose.h,
+                ),
+                s: a.s + (b.s - a.s) * frac,
+                // a point where the path turns back doesn't curve the points around it
+                curvature: if i == 0 || !b.curvature.is_finite() {
+                    a.curvature
+                } else if !a.curvature.is_finite() {
+                    b.curvature
+                } else {
+                    a.curvature + (b.curvature - a.curvature) * frac
+                },
+            }
+        }));
+    }
+    points.extend(path.last().copied());
+    points
+}
+
+#[derive(Copy, Clone)]
+pub struct TrajectoryState {
+    pub t: Duration,
+    pub pose: Pose,
+    pub lin_vel: LinearVelocity,
+    /// counterclockwise positive
+    pub ang_vel: AngularVelocity,
+}
+
+/// Time parameterised poses and velocities
+pub struct Trajectory {
+    states: Vec<TrajectoryState>,
+}
+
+impl Trajectory {
+    /// `states` must be sorted by time
+    pub fn new(states: Vec<TrajectoryState>) -> Self {
+        Trajectory { states }
+    }
+
+    pub fn states(&self) -> &[TrajectoryState] {
+        &self.states
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.states.last().map_or(Duration::ZERO, |state| state.t)
+    }
+
+    /// Linearly interpolates between the states either side of `t`.
+    ///
+    /// Times outside of the trajectory are clamped, `None` if there are no states.
+    pub fn sample(&self, t: Duration) -> Option<TrajectoryState> {
+        let i = self.states.partition_point(|state| state.t <= t);
+        if i == 0 {
+            return self.states.first().copied();
+        }
+        if i == self.states.len() {
+            return self.states.last().copied();
+        }
+
+        let (a, b) = (self.states[i - 1], self.states[i]);
+        let frac = (t - a.t).div_duration_f64(b.t - a.t);
+
+        Some(TrajectoryState {
+            t,
+            pose: Pose::new(
+                a.pose.x + (b.pose.x - a.pose.x) * frac,
+                a.pose.y + (b.pose.y - a.pose.y) * frac,
+                a.pose.h.lerp(b.pose.h, frac),
+            ),
+            lin_vel: a.lin_vel + (b.lin_vel - a.lin_vel) * frac,
+            ang_vel: a.ang_vel + (b.ang_vel - a.ang_vel) * frac,
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use alloc::vec::Vec;
+use core::{f64::consts, time::Duration};
+
+use crate::{
+    float::Float,
+    hal::{BrakeMode, MotorGroup},
+    localisation::{AngleSystem, Localiser, Pose},
+    rt::{sleep_until, Instant, Mutex},
+    tank_chassis::{model::TankModel, TankChassis},
+};
+
+const UPDATE_PERIOD: Duration = Duration::from_millis(10);
+
+/// Follows a list of waypoints by steering towards a point one lookahead
+/// distance along the path.
+pub struct PurePursuit {
+    waypoints: Vec<(f64, f64)>,
+    lookahead: f64,
+    end_tolerance: f64,
+    max_speed: f64,
+    reversed: bool,
+    // progress along the path, the lookahead point never moves backwards
+    segment: usize,
+    target: (f64, f64),
+}
+
+impl PurePursuit {
+    /// The path is finished once the robot is within `end_tolerance` of the last waypoint.
+    pub fn new(waypoints: Vec<(f64, f64)>, lookahead: f64, end_tolerance: f64) -> Self {
+        let target = waypoints.first().copied().unwrap_or((0.0, 0.0));
+        PurePursuit {
+            waypoints,
+            lookahead,
+            end_tolerance,
+            max_speed: 1.0,
+            reversed: false,
+            segment: 0,
+            target,
+        }
+    }
+
+    pub fn set_lookahead(&mut self, lookahead: f64) {
+        self.lookahead = lookahead;
+    }
+
+    /// fraction of the maximum wheel speed
+    pub fn set_max_speed(&mut self, max_speed: f64) {
+        self.max_speed = max_speed;
+    }
+
+    /// drive the path backwards
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    pub fn finished(&self, pose: Pose) -> bool {
+        self.waypoints.last().is_none_or(|&(x, y)| {
+            (x - pose.x.as_mm()).hypot(y - pose.y.as_mm()) < self.end_tolerance
+        })
+    }
+
+    /// Curvature to drive towards the lookahead point, positive to the left.
+    ///
+    /// Returns `None` once the path is finished.
+    pub fn update(&mut self, pose: Pose) -> Option<f64> {
+        if self.finished(pose) {
+            return None;
+        }
+
+        // Furthest intersection of the lookahead circle with the path ahead. Only two
+        // lookaheads of path past the robot are searched, so a path that loops back
+        // near itself isn't skipped ahead. Off the path, the last target is kept.
+        let mut ahead = 0.0;
+        for i in self.segment..self.waypoints.len().saturating_sub(1) {
+            let (start, end) = (self.waypoints[i], self.waypoints[i + 1]);
+            let length = (end.0 - start.0).hypot(end.1 - start.1);
+            if i == self.segment {
+                ahead = -Self::progress(pose, start, end, length);
+            }
+            if ahead > 2.0 * self.lookahead {
+                break;
+            }
+            // past the end of the path, the last waypoint is the target
+            let last = i + 2 == self.waypoints.len();
+            let past_end =
+                last && (end.0 - pose.x.as_mm()).hypot(end.1 - pose.y.as_mm()) < self.lookahead;
+            let target = if past_end {
+                Some(end)
+            } else {
+                self.intersect(pose, start, end)
+            };
+            if let Some(point) = target {
+                self.segment = i;
+                self.target = point;
+            }
+            ahead += length;
+        }
+
+        let mut heading = pose.h.as_rad(AngleSystem::Cartesian);
+        if self.reversed {
+            heading += consts::PI;
+        }
+        let dx = self.target.0 - pose.x.as_mm();
+        let dy = self.target.1 - pose.y.as_mm();
+        let dist_sq = dx * dx + dy * dy;
+        if dist_sq < 1e-9 {
+            return Some(0.0);
+        }
+        // offset of the target to the left of the direction of travel
+        let left_offset = -heading.sin() * dx + heading.cos() * dy;
+        let curvature = 2.0 * left_offset / dist_sq;
+
+        // driving backwards flips the turning direction
+        Some(if self.reversed { -curvature } else { curvature })
+    }
+
+    // distance along a segment to the robot's closest point on it
+    fn progress(pose: Pose, start: (f64, f64), end: (f64, f64), length: f64) -> f64 {
+        if length < 1e-9 {
+            return 0.0;
+        }
+        let (fx, fy) = (pose.x.as_mm() - start.0, pose.y.as_mm() - start.1);
+        ((fx * (end.0 - start.0) + fy * (end.1 - start.1)) / length).clamp(0.0, length)
+    }
+
+    fn intersect(&self, pose: Pose, start: (f64, f64), end: (f64, f64)) -> Option<(f64, f64)> {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let (fx, fy) = (start.0 - pose.x.as_mm(), start.1 - pose.y.as_mm());
+
+        let a = dx * dx + dy * dy;
+        let b = 2.0 * (fx * dx + fy * dy);
+        let c = fx * fx + fy * fy - self.lookahead * self.lookahead;
+        let discriminant = b * b - 4.0 * a * c;
+        if a < 1e-9 || discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let t_far = (-b + root) / (2.0 * a);
+        let t_near = (-b - root) / (2.0 * a);
+        let t = if (0.0..=1.0).contains(&t_far) {
+            t_far
+        } else if (0.0..=1.0).contains(&t_near) {
+            t_near
+        } else {
+            return None;
+        };
+        Some((start.0 + t * dx, start.1 + t * dy))
+    }
+
+    /// Drives the chassis along the path until finished, then brakes.
+    pub async fn follow<M: MotorGroup, L: Localiser>(
+        &mut self,
+        chassis: &Mutex<TankChassis<M>>,
+        localiser: &mut L,
+        model: &TankModel,
+    ) {
+        loop {
+            let time_start = Instant::now();
+
+            localiser.update().await;
+            let Some(curvature) = self.update(localiser.pose()) else {
+                break;
+            };
+
+            let speed = if self.reversed {
+                -self.max_speed
+            } else {
+                self.max_speed
+            };
+            let lin_speed = model.lin_speed();
+            let vels = model.desaturate(model.wheel_vels(lin_speed * speed, curvature));
+            // fractions of the maximum speed
+            chassis
+                .lock()
+                .await
+                .move_velocity(vels.left / lin_speed, vels.right / lin_speed);
+
+            sleep_until(time_start + UPDATE_PERIOD).await;
+        }
+        chassis.lock().await.brake(BrakeMode::Brake);
+    }
+}
@@ -0,0 +1,101 @@
+use core::time::Duration;
+
+use crate::{
+    float::Float,
+    hal::{BrakeMode, MotorGroup},
+    localisation::{Localiser, Pose},
+    rt::{sleep_until, Instant, Mutex},
+    tank_chassis::{
+        kinematics::Twist,
+        model::TankModel,
+        path::{Trajectory, TrajectoryState},
+        TankChassis,
+    },
+    units::{AngularVelocity, LinearVelocity},
+};
+
+const UPDATE_PERIOD: Duration = Duration::from_millis(10);
+
+/// Nonlinear trajectory tracking controller.
+///
+/// `b` (> 0) sets how aggressively position error is corrected and `zeta` (0 to 1)
+/// damps the correction. `b` has units of rad^2 / length^2, so the common
+/// choice of 2.0 for metres is 2e-6 for millimetres.
+pub struct Ramsete {
+    b: f64,
+    zeta: f64,
+}
+
+impl Ramsete {
+    pub fn new(b: f64, zeta: f64) -> Self {
+        Ramsete { b, zeta }
+    }
+
+    pub fn set_gains(&mut self, b: f64, zeta: f64) {
+        self.b = b;
+        self.zeta = zeta;
+    }
+
+    pub fn b(&self) -> f64 {
+        self.b
+    }
+
+    pub fn zeta(&self) -> f64 {
+        self.zeta
+    }
+
+    /// Corrected velocity to track `target` from `pose`.
+    pub fn calculate(&self, target: &TrajectoryState, pose: Pose) -> Twist {
+        // error in the robot frame, x forward
+        let error = target.pose.relative_to(pose);
+        let (e_x, e_y) = (error.x.as_mm(), error.y.as_mm());
+        let e_h = target.pose.h.diff(pose.h);
+
+        let v = target.lin_vel.as_mm_per_sec();
+        let w = target.ang_vel.as_rad_per_sec();
+        let k = 2.0 * self.zeta * (w * w + self.b * v * v).sqrt();
+        let sinc = if e_h.abs() < 1e-9 {
+            1.0
+        } else {
+            e_h.sin() / e_h
+        };
+
+        Twist::new(
+            LinearVelocity::from_mm_per_sec(v * e_h.cos() + k * e_x),
+            AngularVelocity::from_rad_per_sec(w + k * e_h + self.b * v * sinc * e_y),
+        )
+    }
+
+    /// Tracks the trajectory in real time, then brakes.
+    pub async fn follow<M: MotorGroup, L: Localiser>(
+        &self,
+        trajectory: &Trajectory,
+        chassis: &Mutex<TankChassis<M>>,
+        localiser: &mut L,
+        model: &TankModel,
+    ) {
+        let start = Instant::now();
+        loop {
+            let time_start = Instant::now();
+            let t = start.elapsed();
+            if t > trajectory.duration() {
+                break;
+            }
+
+            localiser.update().await;
+            let Some(target) = trajectory.sample(t) else {
+                break;
+            };
+            let twist = self.calculate(&target, localiser.pose());
+
+            let vels = model.desaturate(model.twist_to_wheels(twist));
+            chassis.lock().await.move_velocity(
+                vels.left / model.lin_speed(),
+                vels.right / model.lin_speed(),
+            );
+
+            sleep_until(time_start + UPDATE_PERIOD).await;
+        }
+        chassis.lock().await.brake(BrakeMode::Brake);
+    }
+}
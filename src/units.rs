@@ -0,0 +1,248 @@
+//! Physical quantities with explicit units.
+//!
+//! Each quantity wraps an `f64` in a fixed base unit: millimetres, radians and
+//! seconds. Values are built and read through named conversions, so a distance in
+//! inches can't be passed where millimetres were expected. Time is
+//! [`Duration`](core::time::Duration), which divides lengths and angles into
+//! velocities and velocities into accelerations.
+
+use core::{
+    f64::consts,
+    iter::Sum,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+    time::Duration,
+};
+
+use crate::float::Float;
+
+const INCH: f64 = 25.4;
+/// a field tile is 24 inches square
+const TILE: f64 = 24.0 * INCH;
+const DEGREE: f64 = consts::PI / 180.0;
+const ROTATION: f64 = consts::TAU;
+
+macro_rules! quantity {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Default, PartialEq, PartialOrd, Debug)]
+        pub struct $name(f64);
+
+        impl $name {
+            pub const ZERO: $name = $name(0.0);
+            pub const INFINITY: $name = $name(f64::INFINITY);
+
+            pub fn abs(self) -> $name {
+                $name(self.0.abs())
+            }
+
+            pub fn min(self, other: $name) -> $name {
+                $name(self.0.min(other.0))
+            }
+
+            pub fn max(self, other: $name) -> $name {
+                $name(self.0.max(other.0))
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, rhs: $name) -> $name {
+                $name(self.0 + rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: $name) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, rhs: $name) -> $name {
+                $name(self.0 - rhs.0)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: $name) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+
+        impl Mul<f64> for $name {
+            type Output = $name;
+
+            fn mul(self, rhs: f64) -> $name {
+                $name(self.0 * rhs)
+            }
+        }
+
+        impl Mul<$name> for f64 {
+            type Output = $name;
+
+            fn mul(self, rhs: $name) -> $name {
+                $name(self * rhs.0)
+            }
+        }
+
+        impl Div<f64> for $name {
+            type Output = $name;
+
+            fn div(self, rhs: f64) -> $name {
+                $name(self.0 / rhs)
+            }
+        }
+
+        /// ratio of two quantities
+        impl Div for $name {
+            type Output = f64;
+
+            fn div(self, rhs: $name) -> f64 {
+                self.0 / rhs.0
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = $name>>(iter: I) -> $name {
+                $name(iter.map(|q| q.0).sum())
+            }
+        }
+    };
+}
+
+// named conversions to and from the base unit
+macro_rules! units {
+    ($name:ident { $($(#[$meta:meta])* $from:ident, $as:ident: $scale:expr;)* }) => {
+        impl $name {
+            $(
+                $(#[$meta])*
+                pub const fn $from(value: f64) -> $name {
+                    $name(value * $scale)
+                }
+
+                $(#[$meta])*
+                pub const fn $as(&self) -> f64 {
+                    self.0 / $scale
+                }
+            )*
+        }
+    };
+}
+
+// `quantity / Duration = rate` and `rate * Duration = quantity`
+macro_rules! rate {
+    ($quantity:ident / Duration = $rate:ident) => {
+        impl Div<Duration> for $quantity {
+            type Output = $rate;
+
+            fn div(self, rhs: Duration) -> $rate {
+                $rate(self.0 / rhs.as_secs_f64())
+            }
+        }
+
+        impl Mul<Duration> for $rate {
+            type Output = $quantity;
+
+            fn mul(self, rhs: Duration) -> $quantity {
+                $quantity(self.0 * rhs.as_secs_f64())
+            }
+        }
+    };
+}
+
+quantity!(
+    /// Distance, stored in millimetres
+    Length
+);
+quantity!(
+    /// Relative angle, stored in radians counterclockwise. Directions on the field are
+    /// [`Heading`](crate::localisation::Heading)s.
+    Angle
+);
+quantity!(
+    /// stored in millimetres per second
+    LinearVelocity
+);
+quantity!(
+    /// stored in radians per second counterclockwise
+    AngularVelocity
+);
+quantity!(
+    /// stored in millimetres per second squared
+    LinearAcceleration
+);
+quantity!(
+    /// stored in radians per second squared counterclockwise
+    AngularAcceleration
+);
+quantity!(
+    /// stored in millimetres per second cubed
+    LinearJerk
+);
+
+units!(Length {
+    from_mm, as_mm: 1.0;
+    from_inches, as_inches: INCH;
+    from_tiles, as_tiles: TILE;
+});
+units!(Angle {
+    from_rad, as_rad: 1.0;
+    from_deg, as_deg: DEGREE;
+    from_rotations, as_rotations: ROTATION;
+});
+units!(LinearVelocity {
+    from_mm_per_sec, as_mm_per_sec: 1.0;
+    from_inches_per_sec, as_inches_per_sec: INCH;
+    from_tiles_per_sec, as_tiles_per_sec: TILE;
+});
+units!(AngularVelocity {
+    from_rad_per_sec, as_rad_per_sec: 1.0;
+    from_deg_per_sec, as_deg_per_sec: DEGREE;
+    /// rotations per minute
+    from_rpm, as_rpm: ROTATION / 60.0;
+});
+units!(LinearAcceleration {
+    from_mm_per_sec2, as_mm_per_sec2: 1.0;
+    from_inches_per_sec2, as_inches_per_sec2: INCH;
+    from_tiles_per_sec2, as_tiles_per_sec2: TILE;
+});
+units!(AngularAcceleration {
+    from_rad_per_sec2, as_rad_per_sec2: 1.0;
+    from_deg_per_sec2, as_deg_per_sec2: DEGREE;
+});
+units!(LinearJerk {
+    from_mm_per_sec3, as_mm_per_sec3: 1.0;
+    from_inches_per_sec3, as_inches_per_sec3: INCH;
+    from_tiles_per_sec3, as_tiles_per_sec3: TILE;
+});
+
+rate!(Length / Duration = LinearVelocity);
+rate!(LinearVelocity / Duration = LinearAcceleration);
+rate!(Angle / Duration = AngularVelocity);
+rate!(AngularVelocity / Duration = AngularAcceleration);
+rate!(LinearAcceleration / Duration = LinearJerk);
+
+impl Length {
+    /// length of the hypotenuse with legs `self` and `other`
+    pub fn hypot(self, other: Length) -> Length {
+        Length(self.0.hypot(other.0))
+    }
+}
+
+impl Angle {
+    /// distance rolled by a wheel of `diameter` turning through this angle
+    pub fn arc_length(&self, diameter: Length) -> Length {
+        diameter * (self.0 / 2.0)
+    }
+}
@@ -0,0 +1,74 @@
+use std::f64::consts;
+
+use lib3818_rs::localisation::{wrap_angle, AngleSystem, Heading};
+
+mod common;
+
+use common::{Rng, CASES};
+
+fn rad(heading: Heading) -> f64 {
+    heading.as_rad(AngleSystem::Cartesian)
+}
+
+#[test]
+fn normalises_to_both_ranges() {
+    assert_eq!(wrap_angle(-consts::PI), consts::PI);
+    assert_eq!(wrap_angle(consts::PI), consts::PI);
+    assert_eq!(
+        rad(Heading::new(-consts::FRAC_PI_2).normalised_positive()),
+        1.5 * consts::PI
+    );
+    assert_eq!(rad(Heading::new(consts::TAU).normalised_positive()), 0.0);
+
+    let mut rng = Rng(0x3818);
+    for _ in 0..CASES {
+        let heading = Heading::new(rng.uniform(-50.0, 50.0));
+        let signed = rad(heading.normalised());
+        let positive = rad(heading.normalised_positive());
+        assert!(signed > -consts::PI && signed <= consts::PI);
+        assert!((0.0..consts::TAU).contains(&positive));
+        // still the same direction
+        assert!(heading.approx_eq(heading.normalised(), 1e-9));
+        assert!(heading.approx_eq(heading.normalised_positive(), 1e-9));
+    }
+}
+
+#[test]
+fn diff_takes_the_short_way() {
+    let a = Heading::from_deg(170.0, AngleSystem::Cartesian);
+    let b = Heading::from_deg(-170.0, AngleSystem::Cartesian);
+    assert!((b.diff(a) - 20.0_f64.to_radians()).abs() < 1e-12);
+    assert!((a.diff(b) + 20.0_f64.to_radians()).abs() < 1e-12);
+
+    let mut rng = Rng(0xbeef);
+    for _ in 0..CASES {
+        let a = Heading::new(rng.uniform(-20.0, 20.0));
+        let b = Heading::new(rng.uniform(-20.0, 20.0));
+        let diff = a.diff(b);
+        assert!(diff.abs() <= consts::PI);
+        assert!((b + Heading::new(diff)).approx_eq(a, 1e-9));
+        assert_eq!(rad(-a), -rad(a));
+    }
+}
+
+#[test]
+fn interpolates_and_averages_on_the_circle() {
+    let a = Heading::from_deg(170.0, AngleSystem::Cartesian);
+    let b = Heading::from_deg(-170.0, AngleSystem::Cartesian);
+    let mid = a.lerp(b, 0.5);
+    assert!(mid.approx_eq(Heading::new(consts::PI), 1e-12));
+    assert!(a.lerp(b, 0.0).approx_eq(a, 1e-12));
+    assert!(a.lerp(b, 1.0).approx_eq(b, 1e-12));
+
+    let mean = Heading::mean([a, b, Heading::from_deg(180.0, AngleSystem::Cartesian)]).unwrap();
+    assert!(mean.approx_eq(Heading::new(consts::PI), 1e-12));
+    let weighted = Heading::weighted_mean([
+        (Heading::new(0.0), 3.0),
+        (Heading::new(consts::FRAC_PI_2), 3.0),
+    ])
+    .unwrap();
+    assert!(weighted.approx_eq(Heading::new(consts::FRAC_PI_4), 1e-12));
+
+    assert!(Heading::mean([]).is_none());
+    assert!(Heading::mean([Heading::new(0.0), Heading::new(consts::PI)]).is_none());
+}
@@ -0,0 +1,200 @@
+//! Helpers shared by the integration tests.
+
+// each test crate only uses some of these
+#![allow(dead_code)]
+// the mocks share state through `Rc`, the runtime is single threaded anyway
+#![allow(clippy::arc_with_non_send_sync)]
+
+use std::{
+    f64::consts,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use lib3818_rs::{
+    hal::{
+        mock::{MockDistanceSensor, MockEncoder, MockImu, MockMotor},
+        DistanceReading,
+    },
+    localisation::{
+        field::{Field, SensorMount},
+        heading::TrackerHeading,
+        AngleSystem, ChassisSide, Localiser, Pose, TrackerAxisDrive, TrackerAxisWheel,
+        TrackerDirection, TrackingWheelLocaliser,
+    },
+    rt::{advance, Mutex},
+    sim::TankSim,
+    tank_chassis::{model::TankModel, TankChassis},
+    units::{Length, LinearVelocity},
+};
+
+pub const CASES: usize = 1000;
+
+pub const TRACK_WIDTH: Length = Length::from_mm(254.0);
+// 82.55 mm wheels geared 3:5 from the motors
+pub const WHEEL_DIAMETER: Length = Length::from_mm(82.55 * 0.6);
+pub const TRACKING_WHEEL_DIAMETER: Length = Length::from_mm(50.8);
+pub const MAX_RPM: f64 = 600.0;
+
+// the simulated clock is shared by every test that uses it
+pub static CLOCK: StdMutex<()> = StdMutex::new(());
+
+pub fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+pub fn within(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+// to within a micron, lengths come from chains of trig on mm
+pub fn close_length(a: Length, b: Length) -> bool {
+    (a - b).abs().as_mm() < 1e-6
+}
+
+/// free speed of the drive
+pub fn lin_speed() -> LinearVelocity {
+    LinearVelocity::from_mm_per_sec(MAX_RPM / 60.0 * consts::PI * WHEEL_DIAMETER.as_mm())
+}
+
+// xorshift, so property tests are repeatable without extra dependencies
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn uniform(&mut self, min: f64, max: f64) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        min + (max - min) * (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub type Odometry = TrackingWheelLocaliser<
+    TrackerAxisWheel<MockEncoder>,
+    TrackerAxisDrive<Vec<MockMotor>>,
+    TrackerHeading<TrackerAxisDrive<Vec<MockMotor>>, TrackerAxisDrive<Vec<MockMotor>>>,
+>;
+
+/// The devices of the simulated robot, for the localiser under test to read.
+pub struct Devices {
+    pub chassis: Arc<Mutex<TankChassis<Vec<MockMotor>>>>,
+    /// lateral tracking wheel through the tracking centre
+    pub odom_x: MockEncoder,
+    pub imu: MockImu,
+    pub sensors: Vec<(MockDistanceSensor, SensorMount)>,
+    pub field: Field,
+}
+
+impl Devices {
+    pub fn drive_side(&self, side: ChassisSide) -> TrackerAxisDrive<Vec<MockMotor>> {
+        TrackerAxisDrive::new(self.chassis.clone(), side, TRACK_WIDTH, WHEEL_DIAMETER)
+    }
+
+    /// Tracking wheel odometry with its heading from the drive sides.
+    pub fn odometry(&self, init_pose: Pose) -> Odometry {
+        TrackingWheelLocaliser::new(
+            TrackerAxisWheel::new(self.odom_x.clone(), Length::ZERO, TRACKING_WHEEL_DIAMETER),
+            self.drive_side(ChassisSide::Right),
+            TrackerHeading::new(
+                self.drive_side(ChassisSide::Left),
+                self.drive_side(ChassisSide::Right),
+            ),
+            init_pose,
+        )
+    }
+}
+
+/// A simulated robot driving around an empty field, and a localiser following it.
+pub struct Robot<L> {
+    pub sim: TankSim,
+    pub devices: Devices,
+    pub localiser: L,
+}
+
+impl<L: Localiser> Robot<L> {
+    /// Starts the robot at `truth` with the localiser from `localiser` believing
+    /// it is at `belief`.
+    pub fn new(truth: Pose, belief: Pose, localiser: impl FnOnce(&Devices) -> L) -> Self {
+        let model = TankModel::new(8.0, 1.0, TRACK_WIDTH, lin_speed(), 8.0 * 3000.0);
+        let left = vec![MockMotor::new(MAX_RPM)];
+        let right = vec![MockMotor::new(MAX_RPM)];
+        let odom_x = MockEncoder::new();
+
+        let mut sim = TankSim::new(&model, left.clone(), right.clone(), WHEEL_DIAMETER);
+        sim.add_tracker(
+            odom_x.clone(),
+            TrackerDirection::Lateral,
+            Length::ZERO,
+            TRACKING_WHEEL_DIAMETER,
+        );
+        sim.set_pose(truth);
+
+        // the imu has been running since it was zeroed facing +x
+        let imu = MockImu::new();
+        imu.set_rotation(Some(-truth.h.as_rad(AngleSystem::Cartesian).to_degrees()));
+        let devices = Devices {
+            chassis: Arc::new(Mutex::new(TankChassis::new(left, right))),
+            odom_x,
+            imu,
+            // one facing forward, one facing right
+            sensors: vec![
+                (MockDistanceSensor::new(), SensorMount::new(0.0, 150.0, 0.0)),
+                (
+                    MockDistanceSensor::new(),
+                    SensorMount::new(150.0, 0.0, -consts::FRAC_PI_2),
+                ),
+            ],
+            field: Field::perimeter((0.0, 0.0)),
+        };
+
+        let mut localiser = localiser(&devices);
+        localiser.set_pose(belief);
+        Robot {
+            sim,
+            devices,
+            localiser,
+        }
+    }
+
+    // writes the true pose into the mock sensors
+    pub fn read_sensors(&mut self) {
+        let truth = self.sim.pose();
+        self.devices
+            .imu
+            .set_rotation(Some(-truth.h.as_rad(AngleSystem::Cartesian).to_degrees()));
+        for (sensor, mount) in self.devices.sensors.iter() {
+            let reading =
+                mount
+                    .expected(truth, &self.devices.field)
+                    .map(|distance| DistanceReading {
+                        distance,
+                        confidence: 1.0,
+                        relative_size: 100,
+                    });
+            sensor.set_object(reading);
+        }
+    }
+
+    // one 10 ms loop iteration
+    pub fn tick(&mut self, left: f32, right: f32) {
+        self.read_sensors();
+        self.sim.block_on(self.localiser.update());
+        self.devices
+            .chassis
+            .try_lock()
+            .unwrap()
+            .move_tank(left, right);
+        advance(Duration::from_millis(10));
+        self.sim.update();
+    }
+}
+
+/// A robot at the origin facing +x, and odometry that knows it.
+pub fn robot() -> Robot<Odometry> {
+    let origin = Pose::identity();
+    Robot::new(origin, origin, |devices| devices.odometry(origin))
+}
+
+pub fn dist(a: Pose, b: Pose) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y).as_mm()
+}
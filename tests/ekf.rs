@@ -0,0 +1,132 @@
+use std::f64::consts;
+
+use lib3818_rs::{
+    hal::mock::MockDistanceSensor,
+    localisation::{
+        ekf::{EkfLocaliser, EkfMeasurement, OdometryModel, State},
+        field::{Field, SensorMount},
+        measurement::{DistanceMeasurement, ImuHeadingMeasurement},
+        AngleSystem, Heading, Localiser, Pose,
+    },
+    units::Length,
+};
+
+mod common;
+
+use common::{dist, Odometry, Rng, Robot, CASES, CLOCK};
+
+fn robot(truth: Pose, belief: Pose) -> Robot<EkfLocaliser<Odometry, OdometryModel>> {
+    Robot::new(truth, belief, |devices| {
+        let mut measurements: Vec<Box<dyn EkfMeasurement>> =
+            vec![Box::new(ImuHeadingMeasurement::new(devices.imu.clone()))];
+        for (sensor, mount) in devices.sensors.iter() {
+            measurements.push(Box::new(DistanceMeasurement::new(
+                sensor.clone(),
+                *mount,
+                devices.field.clone(),
+            )));
+        }
+        let mut localiser = EkfLocaliser::new(
+            devices.odometry(belief),
+            OdometryModel::default(),
+            measurements,
+        );
+        localiser.set_spread(80.0, 2.0_f64.to_radians());
+        localiser
+    })
+}
+
+#[test]
+fn distance_jacobian_matches_finite_differences() {
+    let field = Field::perimeter((0.0, 0.0));
+    let mount = SensorMount::new(120.0, 80.0, 0.4);
+    let measurement = DistanceMeasurement::new(MockDistanceSensor::new(), mount, field.clone());
+    let wall = |state: &State| {
+        let (origin, angle) = mount.ray(Pose::new(
+            Length::from_mm(state[0]),
+            Length::from_mm(state[1]),
+            Heading::new(state[2]),
+        ));
+        field.raycast(origin, angle).map(|(_, wall)| *wall)
+    };
+
+    let mut rng = Rng(0x3818);
+    let step = 1e-6;
+    for _ in 0..CASES {
+        let state = State::from_array([
+            rng.uniform(300.0, 3300.0),
+            rng.uniform(300.0, 3300.0),
+            rng.uniform(-consts::PI, consts::PI),
+        ]);
+        let expected = measurement.expected(&state).unwrap();
+        let jacobian = measurement.jacobian(&state).unwrap();
+        for i in 0..3 {
+            let mut nudged = state;
+            nudged[i] += step;
+            // the ray crossed a corner onto another wall
+            if wall(&nudged) != wall(&state) {
+                continue;
+            }
+            let numeric = (measurement.expected(&nudged).unwrap() - expected) / step;
+            assert!(
+                (numeric - jacobian[(0, i)]).abs() <= 1e-3 * numeric.abs().max(1.0),
+                "{i}: {numeric} {}",
+                jacobian[(0, i)]
+            );
+        }
+    }
+}
+
+#[test]
+fn converges_while_driving() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(2800.0),
+        Length::from_mm(600.0),
+        Heading::new(0.0),
+    );
+    let belief = Pose::new(
+        Length::from_mm(2860.0),
+        Length::from_mm(550.0),
+        Heading::new(0.0),
+    );
+    let mut robot = robot(truth, belief);
+
+    for _ in 0..100 {
+        robot.tick(0.25, 0.25);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let truth = robot.sim.pose();
+    let odometry = robot.localiser.odometry().pose();
+    let estimate = robot.localiser.pose();
+    assert!(dist(odometry, truth) > 60.0);
+    assert!(dist(estimate, truth) < 20.0);
+    let h_error =
+        estimate.h.as_rad(AngleSystem::Cartesian) - truth.h.as_rad(AngleSystem::Cartesian);
+    assert!(h_error.abs() < 1.0_f64.to_radians());
+}
+
+#[test]
+fn follows_odometry_while_turning() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(1800.0),
+        Length::from_mm(1800.0),
+        Heading::from_deg(170.0, AngleSystem::Cartesian),
+    );
+    let mut robot = robot(truth, truth);
+    // the sensors never see anything
+    robot.devices.sensors.clear();
+
+    // past 180 degrees, where the heading wraps
+    for _ in 0..100 {
+        robot.tick(0.1, 0.4);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let truth = robot.sim.pose();
+    let estimate = robot.localiser.pose();
+    assert!(dist(estimate, truth) < 15.0);
+    assert!(estimate.h.approx_eq(truth.h, 1.0_f64.to_radians()));
+}
@@ -0,0 +1,65 @@
+use lib3818_rs::{
+    hal::{mock::MockMotor, MotorGroup, MotorTarget},
+    tank_chassis::{feedforward::Feedforward, TankChassis},
+    units::{LinearAcceleration, LinearVelocity},
+};
+
+mod common;
+
+use common::close;
+
+fn voltage(motor: &MockMotor) -> f64 {
+    match motor.target() {
+        MotorTarget::Voltage(volts) => volts,
+        target => panic!("expected a voltage, got {target:?}"),
+    }
+}
+
+#[test]
+fn each_gain_contributes() {
+    let ks = Feedforward::new(0.5, 0.0, 0.0);
+    // static friction opposes the direction of motion, none at rest
+    assert!(close(ks.calculate(100.0, 0.0), 0.5));
+    assert!(close(ks.calculate(-100.0, 0.0), -0.5));
+    assert!(close(ks.calculate(0.0, 500.0), 0.0));
+
+    let kv = Feedforward::new(0.0, 0.01, 0.0);
+    assert!(close(kv.calculate(300.0, 1000.0), 3.0));
+    assert!(close(kv.calculate(-300.0, 0.0), -3.0));
+
+    let ka = Feedforward::new(0.0, 0.0, 0.002);
+    assert!(close(ka.calculate(300.0, 1000.0), 2.0));
+    assert!(close(ka.calculate(300.0, -1000.0), -2.0));
+
+    let all = Feedforward::new(0.5, 0.01, 0.002);
+    assert!(close(all.calculate(300.0, -1000.0), 0.5 + 3.0 - 2.0));
+}
+
+#[test]
+fn chassis_drives_each_side_with_its_feedforward() {
+    let (left, right) = (MockMotor::new(600.0), MockMotor::new(600.0));
+    let mut chassis = TankChassis::new(vec![left.clone()], vec![right.clone()]);
+    chassis.set_feedforward(
+        Feedforward::new(0.5, 0.01, 0.0),
+        Feedforward::new(0.6, 0.01, 0.001),
+    );
+
+    chassis.move_velocity_ff(
+        LinearVelocity::from_mm_per_sec(200.0),
+        LinearVelocity::from_mm_per_sec(-400.0),
+        LinearAcceleration::from_mm_per_sec2(1000.0),
+        LinearAcceleration::from_mm_per_sec2(-1000.0),
+    );
+    assert!(close(voltage(&left), 0.5 + 2.0));
+    assert!(close(voltage(&right), -0.6 - 4.0 - 1.0));
+
+    // clamped to the motors' maximum voltage
+    chassis.move_velocity_ff(
+        LinearVelocity::from_mm_per_sec(5000.0),
+        LinearVelocity::from_mm_per_sec(-5000.0),
+        LinearAcceleration::ZERO,
+        LinearAcceleration::ZERO,
+    );
+    assert!(close(voltage(&left), 12.0));
+    assert!(close(voltage(&right), -12.0));
+}
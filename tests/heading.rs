@@ -0,0 +1,135 @@
+use std::{future::Future, pin::pin, task::Context, time::Duration};
+
+use lib3818_rs::{
+    hal::mock::MockImu,
+    localisation::{
+        heading::{HeadingSource, ImuHeading},
+        AngleSystem, ChassisSide, Heading, Localiser, Pose, TrackerAxisWheel,
+        TrackingWheelLocaliser,
+    },
+    rt::Instant,
+    sim,
+    units::{Angle, Length},
+};
+
+mod common;
+
+use common::{robot, Robot, CLOCK, TRACKING_WHEEL_DIAMETER};
+
+#[test]
+fn imu_waits_for_calibration_and_resets() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let sim = &mut robot().sim;
+    let imu = MockImu::new();
+    imu.set_calibrating(true);
+    imu.set_rotation(Some(90.0));
+    let mut heading = ImuHeading::new(imu.clone());
+    heading.set_scale(0.5);
+    heading.set_heading(Angle::from_rad(1.0));
+
+    let start = Instant::now();
+    let first = {
+        let mut future = pin!(heading.heading());
+        let waker = sim::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        // still calibrating after a few polls
+        for _ in 0..5 {
+            assert!(future.as_mut().poll(&mut cx).is_pending());
+        }
+        imu.set_calibrating(false);
+        sim.block_on(future)
+    };
+    // the first reading after calibrating is the heading that was set
+    assert_eq!(first, Angle::from_rad(1.0));
+    assert!(start.elapsed() >= Duration::from_millis(10));
+
+    // 40 degrees clockwise, halved
+    imu.set_rotation(Some(130.0));
+    let turned = sim.block_on(heading.heading());
+    assert!((turned.as_rad() - (1.0 - 20.0_f64.to_radians())).abs() < 1e-12);
+
+    heading.set_heading(Angle::from_rad(0.0));
+    assert!(sim.block_on(heading.heading()).as_rad().abs() < 1e-12);
+    // disconnected, the last heading is held
+    imu.set_rotation(None);
+    assert!(sim.block_on(heading.heading()).as_rad().abs() < 1e-12);
+}
+
+#[test]
+fn imu_stops_waiting_for_calibration() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let sim = &mut robot().sim;
+    let imu = MockImu::new();
+    imu.set_calibrating(true);
+    let mut heading = ImuHeading::new(imu.clone());
+    heading.set_heading(Angle::from_rad(1.0));
+
+    let start = Instant::now();
+    assert_eq!(sim.block_on(heading.heading()), Angle::from_rad(1.0));
+    assert!(start.elapsed() >= Duration::from_secs(3));
+    // later readings don't wait again, and the first real one keeps the heading
+    assert_eq!(sim.block_on(heading.heading()), Angle::from_rad(1.0));
+    assert!(start.elapsed() < Duration::from_secs(4));
+    imu.set_calibrating(false);
+    imu.set_rotation(Some(45.0));
+    assert_eq!(sim.block_on(heading.heading()), Angle::from_rad(1.0));
+}
+
+#[test]
+fn localiser_uses_imu_heading() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let start = Pose::new(
+        Length::from_mm(500.0),
+        Length::from_mm(500.0),
+        Heading::from_deg(30.0, AngleSystem::Cartesian),
+    );
+    let imu = MockImu::new();
+    let mut robot = Robot::new(start, start, |devices| {
+        let mut imu_heading = ImuHeading::new(imu.clone());
+        imu_heading.set_scale(1.0 / 1.01);
+        TrackingWheelLocaliser::new(
+            TrackerAxisWheel::new(
+                devices.odom_x.clone(),
+                Length::ZERO,
+                TRACKING_WHEEL_DIAMETER,
+            ),
+            devices.drive_side(ChassisSide::Right),
+            imu_heading,
+            start,
+        )
+    });
+    // the sensor reads 1% too far
+    robot.sim.add_imu(imu, 1.01);
+
+    for _ in 0..200 {
+        robot.tick(0.2, 0.6);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let truth = robot.sim.pose();
+    let estimate = robot.localiser.pose();
+    let turned = truth.h.as_deg(AngleSystem::Cartesian) - 30.0;
+    assert!(turned > 180.0);
+    assert!(
+        (estimate.h.as_deg(AngleSystem::Cartesian) - truth.h.as_deg(AngleSystem::Cartesian)).abs()
+            < 0.1
+    );
+    assert!((estimate.x - truth.x).hypot(estimate.y - truth.y).as_mm() < 10.0);
+
+    // resetting the pose resets the heading
+    robot.localiser.set_pose(Pose::new(
+        Length::from_mm(0.0),
+        Length::from_mm(0.0),
+        Heading::new(0.0),
+    ));
+    robot.sim.block_on(robot.localiser.update());
+    assert!(
+        robot
+            .localiser
+            .pose()
+            .h
+            .as_rad(AngleSystem::Cartesian)
+            .abs()
+            < 1e-9
+    );
+}
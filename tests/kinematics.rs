@@ -0,0 +1,107 @@
+use lib3818_rs::{
+    hal::{mock::MockMotor, MotorGroup, MotorTarget},
+    tank_chassis::{
+        kinematics::{self, Twist},
+        model::{TankModel, TankVelocities},
+        TankChassis,
+    },
+    units::{AngularVelocity, Length, LinearVelocity},
+};
+
+mod common;
+
+use common::{close, Rng, CASES};
+
+const TRACK_WIDTH: Length = Length::from_mm(254.0);
+const LIN_SPEED: LinearVelocity = LinearVelocity::from_mm_per_sec(1500.0);
+
+// mm/s and rad/s
+fn twist(linear: f64, angular: f64) -> Twist {
+    Twist::new(
+        LinearVelocity::from_mm_per_sec(linear),
+        AngularVelocity::from_rad_per_sec(angular),
+    )
+}
+
+#[test]
+fn twist_round_trips_through_wheels() {
+    let mut rng = Rng(0x3818);
+    for _ in 0..CASES {
+        let twist = twist(rng.uniform(-2000.0, 2000.0), rng.uniform(-10.0, 10.0));
+        let back = kinematics::twist(kinematics::wheel_vels(twist, TRACK_WIDTH), TRACK_WIDTH);
+        assert!(close(
+            back.linear.as_mm_per_sec(),
+            twist.linear.as_mm_per_sec()
+        ));
+        assert!(close(
+            back.angular.as_rad_per_sec(),
+            twist.angular.as_rad_per_sec()
+        ));
+    }
+}
+
+#[test]
+fn turning_in_place_spins_wheels_opposite() {
+    let vels = kinematics::wheel_vels(twist(0.0, 2.0), TRACK_WIDTH);
+    assert_eq!(vels.left, -vels.right);
+    assert!(close(vels.right.as_mm_per_sec(), TRACK_WIDTH.as_mm()));
+    assert_eq!(twist(0.0, 2.0).curvature(), f64::INFINITY);
+}
+
+#[test]
+fn desaturate_keeps_curvature() {
+    let mut rng = Rng(0xbeef);
+    for _ in 0..CASES {
+        let twist = twist(rng.uniform(-3000.0, 3000.0), rng.uniform(-20.0, 20.0));
+        let vels = kinematics::wheel_vels(twist, TRACK_WIDTH);
+        let limited = kinematics::desaturate(vels, LIN_SPEED);
+
+        let fastest = limited.left.abs().max(limited.right.abs());
+        assert!(fastest <= LIN_SPEED * (1.0 + 1e-9));
+        if vels.left.abs().max(vels.right.abs()) > LIN_SPEED {
+            assert!(close(fastest / LIN_SPEED, 1.0));
+        } else {
+            assert_eq!((limited.left, limited.right), (vels.left, vels.right));
+        }
+
+        let limited_twist = kinematics::twist(limited, TRACK_WIDTH);
+        assert!(close(limited_twist.curvature(), twist.curvature()));
+    }
+}
+
+#[test]
+fn model_desaturates_to_lin_speed() {
+    let model = TankModel::builder(TRACK_WIDTH, LIN_SPEED).build();
+    let vels = model.desaturate(model.twist_to_wheels(twist(1500.0, 5.0)));
+    assert!(close(vels.right / LIN_SPEED, 1.0));
+    assert!(vels.left > LinearVelocity::ZERO && vels.left < vels.right);
+
+    let speed = LinearVelocity::from_mm_per_sec(1000.0);
+    let twist = model.wheels_to_twist(TankVelocities {
+        left: speed,
+        right: speed,
+    });
+    assert_eq!(
+        (twist.linear, twist.angular),
+        (speed, AngularVelocity::ZERO)
+    );
+}
+
+#[test]
+fn move_tank_scales_down_both_sides() {
+    let left = MockMotor::new(600.0);
+    let right = MockMotor::new(600.0);
+    let mut chassis = TankChassis::new(left.clone(), right.clone());
+
+    chassis.move_tank(2.0, 1.0);
+    assert_eq!(left.target(), MotorTarget::Voltage(12.0));
+    assert_eq!(right.target(), MotorTarget::Voltage(6.0));
+
+    chassis.move_tank(0.5, -0.25);
+    assert_eq!(left.target(), MotorTarget::Voltage(6.0));
+    assert_eq!(right.target(), MotorTarget::Voltage(-3.0));
+
+    chassis.move_velocity(-0.5, -1.5);
+    assert!(matches!(left.target(), MotorTarget::Velocity(rpm) if close(rpm, -200.0)));
+    assert!(matches!(right.target(), MotorTarget::Velocity(rpm) if close(rpm, -600.0)));
+}
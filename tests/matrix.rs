@@ -0,0 +1,30 @@
+use lib3818_rs::matrix::{Matrix, Vector};
+
+#[test]
+fn multiplies_and_transposes() {
+    let a = Matrix::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    let b = Matrix::new([[7.0, 8.0], [9.0, 10.0], [11.0, 12.0]]);
+    assert_eq!(a * b, Matrix::new([[58.0, 64.0], [139.0, 154.0]]));
+    assert_eq!((a * b).transpose(), b.transpose() * a.transpose());
+
+    let v = Vector::from_array([1.0, -1.0, 2.0]);
+    assert_eq!(a * v, Vector::from_array([5.0, 11.0]));
+    assert_eq!(v.dot(&v), 6.0);
+    assert_eq!(Matrix::<3, 3>::identity() * v, v);
+}
+
+#[test]
+fn cholesky_reconstructs() {
+    let a = Matrix::new([[4.0, 2.0, 0.4], [2.0, 10.0, 1.0], [0.4, 1.0, 3.0]]);
+    let l = a.cholesky().unwrap();
+    assert_eq!(l[(0, 1)], 0.0);
+    let back = l * l.transpose();
+    for i in 0..3 {
+        for j in 0..3 {
+            assert!((back[(i, j)] - a[(i, j)]).abs() < 1e-12);
+        }
+    }
+
+    // not positive definite
+    assert!(Matrix::new([[1.0, 2.0], [2.0, 1.0]]).cholesky().is_none());
+}
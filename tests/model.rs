@@ -0,0 +1,139 @@
+use lib3818_rs::{
+    tank_chassis::model::{TankModel, TankModelBuilder},
+    units::{Length, LinearAcceleration, LinearVelocity},
+};
+
+mod common;
+
+use common::{close, Rng, CASES};
+
+// mm and mm/s
+const TRACK_WIDTH: f64 = 254.0;
+const LIN_SPEED: f64 = 1500.0;
+
+fn builder() -> TankModelBuilder {
+    TankModel::builder(
+        Length::from_mm(TRACK_WIDTH),
+        LinearVelocity::from_mm_per_sec(LIN_SPEED),
+    )
+}
+
+fn model() -> TankModel {
+    builder()
+        .mass(8.0)
+        .force(8.0 * 3000.0)
+        .lateral_mu(0.8)
+        .build()
+}
+
+#[test]
+fn builder_defaults_are_unlimited() {
+    let model = builder().build();
+    assert_eq!(model.track_width().as_mm(), TRACK_WIDTH);
+    assert_eq!(model.lin_speed().as_mm_per_sec(), LIN_SPEED);
+    assert_eq!(model.max_accel(), LinearAcceleration::INFINITY);
+
+    let constraints = model.constraints(0.01);
+    assert!(close(
+        constraints.max_vel.as_mm_per_sec(),
+        LIN_SPEED / (1.0 + 0.01 * TRACK_WIDTH / 2.0)
+    ));
+    assert_eq!(constraints.max_accel, LinearAcceleration::INFINITY);
+}
+
+#[test]
+fn straight_line() {
+    let model = model();
+    let vels = model.wheel_vels(LinearVelocity::from_mm_per_sec(1000.0), 0.0);
+    assert_eq!(vels.left.as_mm_per_sec(), 1000.0);
+    assert_eq!(vels.right.as_mm_per_sec(), 1000.0);
+
+    let constraints = model.constraints(0.0);
+    assert_eq!(constraints.max_vel.as_mm_per_sec(), LIN_SPEED);
+    assert!(close(constraints.max_accel.as_mm_per_sec2(), 3000.0));
+}
+
+#[test]
+fn turn_in_place() {
+    let model = model();
+    let speed = LinearVelocity::from_mm_per_sec(500.0);
+    let left = model.wheel_vels(speed, f64::INFINITY);
+    assert_eq!((left.left, left.right), (-speed, speed));
+    let right = model.wheel_vels(speed, f64::NEG_INFINITY);
+    assert_eq!((right.left, right.right), (speed, -speed));
+
+    for curvature in [f64::INFINITY, f64::NEG_INFINITY] {
+        let constraints = model.constraints(curvature);
+        assert_eq!(constraints.max_vel, LinearVelocity::ZERO);
+        assert_eq!(constraints.max_accel, LinearAcceleration::ZERO);
+    }
+    assert!(close(
+        model.max_ang_vel().as_rad_per_sec(),
+        2.0 * LIN_SPEED / TRACK_WIDTH
+    ));
+    assert!(close(
+        model.max_ang_accel().as_rad_per_sec2(),
+        2.0 * 3000.0 / TRACK_WIDTH
+    ));
+}
+
+#[test]
+fn wheel_vels_reproduce_curvature() {
+    let model = model();
+    let mut rng = Rng(0x3818);
+    for _ in 0..CASES {
+        let vel = rng.uniform(-LIN_SPEED, LIN_SPEED);
+        let curvature = rng.uniform(-0.05, 0.05);
+        let vels = model.wheel_vels(LinearVelocity::from_mm_per_sec(vel), curvature);
+
+        let lin_vel = (vels.left + vels.right).as_mm_per_sec() / 2.0;
+        let ang_vel = (vels.right - vels.left).as_mm_per_sec() / TRACK_WIDTH;
+        assert!(close(lin_vel, vel));
+        assert!((ang_vel / lin_vel - curvature).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn constrained_wheels_stay_within_limits() {
+    let model = model();
+    let mut rng = Rng(0xbeef);
+    for _ in 0..CASES {
+        let curvature = rng.uniform(-0.05, 0.05);
+        let constraints = model.constraints(curvature);
+        let max_vel = constraints.max_vel.as_mm_per_sec();
+        let max_accel = constraints.max_accel.as_mm_per_sec2();
+        let outer_scale = 1.0 + curvature.abs() * TRACK_WIDTH / 2.0;
+
+        // the outer wheel is at the speed limit unless friction limits it first
+        let vels = model.wheel_vels(constraints.max_vel, curvature);
+        let outer = vels.left.abs().max(vels.right.abs()).as_mm_per_sec();
+        assert!(outer <= LIN_SPEED * (1.0 + 1e-9));
+        let friction_vel = (0.8 * 9806.65 / curvature.abs()).sqrt();
+        assert!(close(outer, LIN_SPEED) || close(max_vel, friction_vel));
+
+        // centripetal acceleration within lateral friction
+        let centripetal = max_vel.powi(2) * curvature.abs();
+        assert!(centripetal <= 0.8 * 9806.65 * (1.0 + 1e-9));
+
+        // outer wheel acceleration within the drive's
+        assert!(max_accel * outer_scale <= model.max_accel().as_mm_per_sec2() * (1.0 + 1e-9));
+    }
+}
+
+#[test]
+fn friction_limits_tight_turns() {
+    let model = builder().lateral_mu(0.1).build();
+    // 200 mm radius
+    let max_vel = model.constraints(1.0 / 200.0).max_vel.as_mm_per_sec();
+    assert!(close(max_vel, (0.1 * 9806.65 * 200.0_f64).sqrt()));
+    assert!(max_vel < LIN_SPEED / (1.0 + TRACK_WIDTH / 400.0));
+}
+
+#[test]
+fn no_grip_only_drives_straight() {
+    let model = builder().lateral_mu(0.0).build();
+    let straight = model.constraints(0.0).max_vel;
+    assert_eq!(straight.as_mm_per_sec(), LIN_SPEED);
+    let curved = model.constraints(1.0 / 200.0).max_vel;
+    assert_eq!(curved, LinearVelocity::ZERO);
+}
@@ -0,0 +1,174 @@
+use std::{f64::consts, time::Duration};
+
+use lib3818_rs::{
+    arm::{Arm, ArmSignal},
+    hal::{mock::MockMotor, BrakeMode, MotorGroup},
+    rt::advance,
+    sim::{
+        motor::{DcMotor, Gearset, SimMotor},
+        TankSim,
+    },
+    tank_chassis::{model::TankModel, TankChassis},
+    units::{Length, LinearVelocity},
+};
+
+mod common;
+
+use common::CLOCK;
+
+const DT: f64 = 0.001;
+
+fn sim_motor(gearset: Gearset, inertia: f64) -> (SimMotor, MockMotor) {
+    let device = MockMotor::new(gearset.max_rpm());
+    let motor = SimMotor::new(DcMotor::new(gearset), device.clone(), inertia);
+    (motor, device)
+}
+
+fn run(motor: &mut SimMotor, seconds: f64) {
+    for _ in 0..(seconds / DT) as usize {
+        motor.step(DT);
+    }
+}
+
+fn rpm(motor: &SimMotor) -> f64 {
+    motor.velocity() * 60.0 / consts::TAU
+}
+
+#[test]
+fn free_speed_matches_gearset() {
+    for gearset in [Gearset::Red, Gearset::Green, Gearset::Blue] {
+        let free_rpm = DcMotor::new(gearset).free_speed() * 60.0 / consts::TAU;
+        assert!((free_rpm - gearset.max_rpm()).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn stall_torque_is_current_limited() {
+    assert!((DcMotor::new(Gearset::Red).stall_torque() - 2.1).abs() < 1e-9);
+    assert!((DcMotor::new(Gearset::Green).stall_torque() - 1.05).abs() < 1e-9);
+    assert!((DcMotor::new(Gearset::Blue).stall_torque() - 0.35).abs() < 1e-9);
+
+    let mut motor = DcMotor::new(Gearset::Red);
+    motor.set_current_limit(1.25);
+    assert!((motor.stall_torque() - 1.05).abs() < 1e-9);
+}
+
+#[test]
+fn full_voltage_settles_at_free_speed() {
+    let (mut motor, mut device) = sim_motor(Gearset::Green, 0.001);
+    device.set_voltage(12.0);
+    run(&mut motor, 1.0);
+
+    assert!((rpm(&motor) - 200.0).abs() < 0.5);
+    assert!((device.velocity() - rpm(&motor)).abs() < 1e-9);
+}
+
+#[test]
+fn viscous_friction_lowers_speed() {
+    let mut device = MockMotor::new(200.0);
+    let mut dc_motor = DcMotor::new(Gearset::Green);
+    dc_motor.set_friction(0.01, 0.0);
+    let mut motor = SimMotor::new(dc_motor, device.clone(), 0.001);
+    device.set_voltage(12.0);
+    run(&mut motor, 1.0);
+
+    assert!(rpm(&motor) < 190.0 && rpm(&motor) > 100.0);
+}
+
+#[test]
+fn coulomb_friction_stops_coasting_motor() {
+    let mut device = MockMotor::new(200.0);
+    let mut dc_motor = DcMotor::new(Gearset::Green);
+    dc_motor.set_friction(0.0, 0.05);
+    let mut motor = SimMotor::new(dc_motor, device.clone(), 0.001);
+
+    device.set_voltage(12.0);
+    run(&mut motor, 0.5);
+    assert!(rpm(&motor) > 100.0);
+
+    device.brake(BrakeMode::Coast);
+    run(&mut motor, 1.0);
+    assert_eq!(motor.velocity(), 0.0);
+    let position = motor.position();
+    run(&mut motor, 0.5);
+    assert_eq!(motor.position(), position);
+}
+
+#[test]
+fn position_target_is_reached() {
+    let (mut motor, mut device) = sim_motor(Gearset::Red, 0.01);
+    device.set_position_target(90.0, 50.0);
+    run(&mut motor, 0.2);
+    // limited to the requested rpm
+    assert!(rpm(&motor) <= 50.5);
+
+    run(&mut motor, 1.5);
+    assert!((device.position() - 90.0).abs() < 1.0);
+}
+
+#[test]
+fn hold_keeps_position_under_brake() {
+    let (mut motor, mut device) = sim_motor(Gearset::Green, 0.001);
+    device.set_voltage(12.0);
+    run(&mut motor, 0.2);
+    let held = device.position();
+    device.brake(BrakeMode::Hold);
+
+    // overshoots while stopping, then returns to where the brake was applied
+    run(&mut motor, 1.0);
+    assert!((device.position() - held).abs() < 1.0);
+    assert!(rpm(&motor).abs() < 1.0);
+}
+
+#[test]
+fn arm_returns_to_accepting() {
+    let (mut lift, lift_device) = sim_motor(Gearset::Green, 0.05);
+    let (mut wrist, wrist_device) = sim_motor(Gearset::Red, 0.02);
+    let mut arm = Arm::new(lift_device, wrist_device);
+
+    let mut elapsed = 0.0;
+    while arm.state() != "accepting" && elapsed < 5.0 {
+        arm.update(ArmSignal::Empty);
+        arm.act();
+        run(&mut lift, 0.02);
+        run(&mut wrist, 0.02);
+        elapsed += 0.02;
+    }
+    assert_eq!(arm.state(), "accepting");
+}
+
+#[test]
+fn tank_sim_drive_motors_reach_free_speed() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // 82.55 mm wheels geared 3:5 from blue motors
+    let wheel_diameter = 82.55 * 0.6;
+    let lin_speed = 600.0 / 60.0 * consts::PI * wheel_diameter;
+    let model = TankModel::new(
+        8.0,
+        1.0,
+        Length::from_mm(254.0),
+        LinearVelocity::from_mm_per_sec(lin_speed),
+        0.0,
+    );
+
+    let left = vec![MockMotor::new(600.0), MockMotor::new(600.0)];
+    let right = vec![MockMotor::new(600.0), MockMotor::new(600.0)];
+    let mut sim = TankSim::new(
+        &model,
+        left.clone(),
+        right.clone(),
+        Length::from_mm(wheel_diameter),
+    );
+    sim.set_drive_motor(Some(DcMotor::new(Gearset::Blue)));
+    let mut chassis = TankChassis::new(left, right);
+
+    chassis.move_tank(1.0, 1.0);
+    advance(Duration::from_millis(100));
+    sim.update();
+    // stall force is 4 * 0.35 N m over a 24.8 mm radius, about 7 m/s^2 for 8 kg
+    assert!(sim.lin_vel() > 500.0 && sim.lin_vel() < 800.0);
+
+    advance(Duration::from_secs(2));
+    sim.update();
+    assert!((sim.lin_vel() - lin_speed).abs() < 0.01 * lin_speed);
+}
@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+use lib3818_rs::{
+    tank_chassis::{
+        model::TankModel,
+        mp::{MotionProfile, ProfileState, SCurveProfile, TankConstraints, TrapezoidProfile},
+    },
+    units::{Length, LinearAcceleration, LinearJerk, LinearVelocity},
+};
+
+mod common;
+
+use common::{close, within};
+
+const DT: Duration = Duration::from_millis(1);
+
+fn constraints(max_vel: f64, max_accel: f64, max_jerk: f64) -> TankConstraints {
+    TankConstraints {
+        max_vel: LinearVelocity::from_mm_per_sec(max_vel),
+        max_accel: LinearAcceleration::from_mm_per_sec2(max_accel),
+        max_jerk: LinearJerk::from_mm_per_sec3(max_jerk),
+    }
+}
+
+// every sample is within the limits, and position is the integral of velocity
+fn assert_follows_limits(states: &[ProfileState], constraints: &TankConstraints) {
+    let max_vel = constraints.max_vel.as_mm_per_sec();
+    let max_accel = constraints.max_accel.as_mm_per_sec2();
+    for pair in states.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let dt = (b.t - a.t).as_secs_f64();
+        assert!(b.vel.as_mm_per_sec().abs() <= max_vel + 1e-6);
+        assert!(b.accel.as_mm_per_sec2().abs() <= max_accel + 1e-6);
+        let travelled = (b.pos - a.pos).as_mm();
+        let mean_vel = (a.vel + b.vel).as_mm_per_sec() / 2.0;
+        assert!(within(travelled, mean_vel * dt, max_accel * dt * dt));
+    }
+}
+
+#[test]
+fn trapezoid_cruises_at_max_velocity() {
+    let constraints = constraints(1000.0, 2000.0, f64::INFINITY);
+    // half a second to accelerate over 250 mm, then 1.5 s cruising
+    let profile = TrapezoidProfile::new(Length::from_mm(2000.0), &constraints);
+    assert!(close(profile.duration().as_secs_f64(), 2.5));
+    assert!(close(
+        profile
+            .sample(Duration::from_millis(250))
+            .vel
+            .as_mm_per_sec(),
+        500.0
+    ));
+    assert!(close(
+        profile.sample(Duration::from_secs(1)).pos.as_mm(),
+        750.0
+    ));
+
+    let states = profile.samples(DT);
+    assert_follows_limits(&states, &constraints);
+    let end = states[states.len() - 1];
+    assert!(close(end.pos.as_mm(), 2000.0));
+    assert_eq!(end.vel, LinearVelocity::ZERO);
+}
+
+#[test]
+fn short_trapezoid_is_triangular() {
+    let constraints = constraints(1000.0, 2000.0, f64::INFINITY);
+    // too short to reach 1000 mm/s, which takes 250 mm each way
+    let profile = TrapezoidProfile::new(Length::from_mm(200.0), &constraints);
+    let peak_vel = (200.0_f64 * 2000.0).sqrt();
+    let half = profile.duration() / 2;
+    assert!(close(half.as_secs_f64(), peak_vel / 2000.0));
+
+    let peak = profile.sample(half);
+    assert!(within(peak.vel.as_mm_per_sec(), peak_vel, 1e-6));
+    assert!(within(peak.pos.as_mm(), 100.0, 1e-6));
+    assert!(profile
+        .samples(DT)
+        .iter()
+        .all(|state| state.vel.as_mm_per_sec() <= peak_vel + 1e-6));
+    assert_follows_limits(&profile.samples(DT), &constraints);
+}
+
+#[test]
+fn negative_distances_mirror() {
+    let constraints = constraints(1000.0, 2000.0, f64::INFINITY);
+    let forward = TrapezoidProfile::new(Length::from_mm(800.0), &constraints);
+    let backward = TrapezoidProfile::new(Length::from_mm(-800.0), &constraints);
+    assert_eq!(forward.duration(), backward.duration());
+    for (f, b) in forward.samples(DT).iter().zip(backward.samples(DT)) {
+        assert_eq!(f.pos, -b.pos);
+        assert_eq!(f.vel, -b.vel);
+        assert_eq!(f.accel, -b.accel);
+    }
+}
+
+#[test]
+fn samples_past_the_end_hold_the_final_state() {
+    let constraints = constraints(1000.0, 2000.0, f64::INFINITY);
+    let profile = TrapezoidProfile::new(Length::from_mm(500.0), &constraints);
+    let after = profile.sample(profile.duration() + Duration::from_secs(3));
+    assert_eq!(after.t, profile.duration());
+    assert!(close(after.pos.as_mm(), 500.0));
+    assert_eq!(after.vel, LinearVelocity::ZERO);
+    assert_eq!(after.accel, LinearAcceleration::ZERO);
+}
+
+#[test]
+fn sampling_with_no_period_gives_the_end() {
+    let constraints = constraints(1000.0, 2000.0, f64::INFINITY);
+    let profile = TrapezoidProfile::new(Length::from_mm(500.0), &constraints);
+    let states = profile.samples(Duration::ZERO);
+    assert_eq!(states.len(), 1);
+    assert_eq!(states[0].t, profile.duration());
+    assert!(close(states[0].pos.as_mm(), 500.0));
+}
+
+#[test]
+fn s_curve_limits_jerk() {
+    let constraints = constraints(1000.0, 2000.0, 10000.0);
+    // 0.2 s jerk phases and 0.3 s at full acceleration cover 350 mm each way
+    let profile = SCurveProfile::new(Length::from_mm(2000.0), &constraints);
+    assert!(close(profile.duration().as_secs_f64(), 2.7));
+    let cruise = profile.sample(Duration::from_secs(1));
+    assert!(close(cruise.vel.as_mm_per_sec(), 1000.0));
+    assert!(close(cruise.pos.as_mm(), 650.0));
+
+    let states = profile.samples(DT);
+    assert_follows_limits(&states, &constraints);
+    for pair in states.windows(2) {
+        let change = (pair[1].accel - pair[0].accel).as_mm_per_sec2();
+        assert!(change.abs() <= 10000.0 * DT.as_secs_f64() + 1e-6);
+    }
+    let end = states[states.len() - 1];
+    assert!(close(end.pos.as_mm(), 2000.0));
+    assert_eq!(end.vel, LinearVelocity::ZERO);
+}
+
+#[test]
+fn short_s_curves_stop_short_of_the_limits() {
+    let constraints = constraints(1000.0, 2000.0, 10000.0);
+    // too short to reach either the acceleration or velocity limit
+    for distance in [20.0, 200.0] {
+        let profile = SCurveProfile::new(Length::from_mm(distance), &constraints);
+        let states = profile.samples(DT);
+        assert_follows_limits(&states, &constraints);
+        let end = states[states.len() - 1];
+        assert!(close(end.pos.as_mm(), distance));
+        assert_eq!(end.vel, LinearVelocity::ZERO);
+        // symmetric, so peak velocity is at the middle
+        let peak = profile.sample(profile.duration() / 2).vel;
+        assert!(states.iter().all(|state| state.vel <= peak));
+    }
+}
+
+#[test]
+fn infinite_jerk_s_curve_matches_trapezoid() {
+    let constraints = constraints(1000.0, 2000.0, f64::INFINITY);
+    for distance in [-2000.0, 200.0] {
+        let trapezoid = TrapezoidProfile::new(Length::from_mm(distance), &constraints);
+        let s_curve = SCurveProfile::new(Length::from_mm(distance), &constraints);
+        assert!(close(
+            trapezoid.duration().as_secs_f64(),
+            s_curve.duration().as_secs_f64()
+        ));
+        for (t, s) in trapezoid.samples(DT).iter().zip(s_curve.samples(DT)) {
+            assert!(within(t.pos.as_mm(), s.pos.as_mm(), 1e-6));
+            assert!(within(t.vel.as_mm_per_sec(), s.vel.as_mm_per_sec(), 1e-6));
+        }
+    }
+}
+
+// both kinds of profile over `distance`
+fn profiles(distance: f64, constraints: &TankConstraints) -> [Box<dyn MotionProfile>; 2] {
+    [
+        Box::new(TrapezoidProfile::new(
+            Length::from_mm(distance),
+            constraints,
+        )),
+        Box::new(SCurveProfile::new(Length::from_mm(distance), constraints)),
+    ]
+}
+
+#[test]
+fn zero_limits_never_arrive() {
+    let model = TankModel::builder(
+        Length::from_mm(254.0),
+        LinearVelocity::from_mm_per_sec(1500.0),
+    )
+    .mass(8.0)
+    .force(8.0 * 3000.0)
+    .lateral_mu(0.8)
+    .build();
+    // turning in place has no linear motion
+    for profile in profiles(500.0, &model.constraints(f64::INFINITY))
+        .into_iter()
+        .chain(
+            profiles(500.0, &constraints(1000.0, 2000.0, 0.0))
+                .into_iter()
+                .skip(1),
+        )
+    {
+        assert_eq!(profile.duration(), Duration::MAX);
+        let later = profile.sample(Duration::from_secs(10));
+        assert_eq!(later.pos, Length::ZERO);
+        assert_eq!(later.vel, LinearVelocity::ZERO);
+        assert_eq!(profile.samples(DT).len(), 1);
+    }
+}
+
+#[test]
+fn zero_distance_takes_no_time() {
+    for constraints in [
+        constraints(1000.0, 2000.0, 10000.0),
+        constraints(0.0, 0.0, 0.0),
+    ] {
+        for profile in profiles(0.0, &constraints) {
+            assert_eq!(profile.duration(), Duration::ZERO);
+            assert_eq!(profile.sample(Duration::from_secs(1)).pos, Length::ZERO);
+        }
+    }
+}
+
+#[test]
+fn infinite_limits_are_never_reached() {
+    for profile in profiles(
+        500.0,
+        &constraints(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+    ) {
+        assert_eq!(profile.duration(), Duration::ZERO);
+        assert!(close(profile.sample(Duration::ZERO).pos.as_mm(), 500.0));
+    }
+
+    // cruising the whole way
+    for profile in profiles(500.0, &constraints(1000.0, f64::INFINITY, f64::INFINITY)) {
+        assert!(close(profile.duration().as_secs_f64(), 0.5));
+        let half = profile.sample(Duration::from_millis(250));
+        assert!(close(half.pos.as_mm(), 250.0));
+        assert!(close(half.vel.as_mm_per_sec(), 1000.0));
+        assert!(close(profile.sample(profile.duration()).pos.as_mm(), 500.0));
+    }
+
+    // limited by jerk alone, so the same as an acceleration limit that's never reached
+    let jerk_only = SCurveProfile::new(
+        Length::from_mm(500.0),
+        &constraints(f64::INFINITY, f64::INFINITY, 10000.0),
+    );
+    let mut states = jerk_only.samples(DT);
+    let end = states.pop().unwrap();
+    assert!(close(end.pos.as_mm(), 500.0));
+    assert!(states.iter().all(|state| state.pos.as_mm().is_finite()));
+    let peak = jerk_only.sample(jerk_only.duration() / 2);
+    assert!(within(peak.pos.as_mm(), 250.0, 1e-6));
+}
@@ -0,0 +1,125 @@
+use std::f64::consts;
+
+use lib3818_rs::{
+    hal::mock::MockDistanceSensor,
+    localisation::{
+        field::{Field, SensorMount, Wall},
+        particle::ParticleFilterLocaliser,
+        AngleSystem, Heading, Localiser, Pose,
+    },
+    units::Length,
+};
+
+mod common;
+
+use common::{dist, Odometry, Robot, CLOCK};
+
+fn robot(
+    truth: Pose,
+    belief: Pose,
+) -> Robot<ParticleFilterLocaliser<Odometry, MockDistanceSensor>> {
+    Robot::new(truth, belief, |devices| {
+        let mut localiser = ParticleFilterLocaliser::new(
+            devices.odometry(belief),
+            devices.field.clone(),
+            devices.sensors.clone(),
+            300,
+        );
+        localiser.set_spread(80.0, 3.0_f64.to_radians());
+        localiser
+    })
+}
+
+#[test]
+fn raycast_hits_nearest_wall() {
+    let field = Field::perimeter((0.0, 0.0));
+    let (dist, wall) = field.raycast((1000.0, 500.0), -consts::FRAC_PI_2).unwrap();
+    assert!((dist - 500.0).abs() < 1e-9);
+    assert_eq!(*wall, Wall::new((0.0, 0.0), (3657.6, 0.0)));
+
+    let (dist, _) = field.raycast((1000.0, 1000.0), consts::FRAC_PI_4).unwrap();
+    assert!((dist - (3657.6 - 1000.0) * consts::SQRT_2).abs() < 1e-6);
+
+    let mount = SensorMount::new(100.0, 0.0, -consts::FRAC_PI_2);
+    let pose = Pose::new(
+        Length::from_mm(1000.0),
+        Length::from_mm(1000.0),
+        Heading::new(0.0),
+    );
+    // facing +x, so the right side faces -y
+    assert!((mount.expected(pose, &field).unwrap() - 900.0).abs() < 1e-9);
+}
+
+#[test]
+fn converges_while_driving() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(2800.0),
+        Length::from_mm(600.0),
+        Heading::new(0.0),
+    );
+    let belief = Pose::new(
+        Length::from_mm(2860.0),
+        Length::from_mm(550.0),
+        Heading::new(0.0),
+    );
+    let mut robot = robot(truth, belief);
+
+    for _ in 0..100 {
+        robot.tick(0.25, 0.25);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let truth = robot.sim.pose();
+    let odometry = robot.localiser.odometry().pose();
+    let estimate = robot.localiser.pose();
+    assert!(dist(odometry, truth) > 60.0);
+    assert!(dist(estimate, truth) < 20.0);
+    let h_error =
+        estimate.h.as_rad(AngleSystem::Cartesian) - truth.h.as_rad(AngleSystem::Cartesian);
+    assert!(h_error.abs() < 2.0_f64.to_radians());
+}
+
+#[test]
+fn converges_while_stationary() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(3000.0),
+        Length::from_mm(800.0),
+        Heading::new(0.0),
+    );
+    let belief = Pose::new(
+        Length::from_mm(2950.0),
+        Length::from_mm(850.0),
+        Heading::new(0.0),
+    );
+    let mut robot = robot(truth, belief);
+
+    for _ in 0..50 {
+        robot.tick(0.0, 0.0);
+    }
+    assert!(dist(robot.localiser.pose(), truth) < 20.0);
+    assert_eq!(robot.localiser.particles().len(), 300);
+}
+
+#[test]
+fn follows_odometry_without_readings() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(1800.0),
+        Length::from_mm(1800.0),
+        Heading::new(0.0),
+    );
+    let mut robot = robot(truth, truth);
+    // the sensors never see anything
+    robot.devices.sensors.clear();
+
+    for _ in 0..100 {
+        robot.tick(0.3, 0.3);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let odometry = robot.localiser.odometry().pose();
+    assert!(dist(robot.localiser.pose(), odometry) < 20.0);
+    assert!(robot.localiser.pose().x > truth.x + Length::from_mm(150.0));
+}
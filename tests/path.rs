@@ -0,0 +1,118 @@
+use std::{f64::consts, time::Duration};
+
+use lib3818_rs::{
+    tank_chassis::{
+        model::TankModel,
+        mp::{MotionProfile, TrapezoidProfile},
+        path::{Path, PathProfile},
+    },
+    units::{Length, LinearVelocity},
+};
+
+fn model() -> TankModel {
+    TankModel::builder(
+        Length::from_mm(254.0),
+        LinearVelocity::from_mm_per_sec(1500.0),
+    )
+    .mass(8.0)
+    .force(8.0 * 3000.0)
+    .lateral_mu(0.8)
+    .build()
+}
+
+#[test]
+fn two_point_path_is_profiled() {
+    let model = model();
+    let path = Path::from_points(&[(0.0, 0.0), (1000.0, 0.0)]);
+    let profile = PathProfile::new(&path, &model);
+
+    // the same motion as a trapezoid, up to the spacing of the profiled points
+    let trapezoid = TrapezoidProfile::new(Length::from_mm(1000.0), &model.constraints(0.0));
+    let duration = profile.duration().as_secs_f64();
+    assert!((duration - trapezoid.duration().as_secs_f64()).abs() < 0.02 * duration);
+
+    let cruise = profile.sample(profile.duration() / 2);
+    assert!((cruise.vel.as_mm_per_sec() - 1500.0).abs() < 1e-6);
+    assert!((cruise.pos.as_mm() - 500.0).abs() < 1.0);
+    let end = profile.sample(profile.duration());
+    assert!((end.pos.as_mm() - 1000.0).abs() < 1e-9);
+    assert_eq!(end.vel, LinearVelocity::ZERO);
+
+    let mid = profile.point_at(profile.duration() / 2).unwrap();
+    assert!((mid.pose.x.as_mm() - 500.0).abs() < 10.0);
+}
+
+#[test]
+fn curves_are_slower() {
+    let model = model();
+    // half circle of radius 500 mm
+    let xy: Vec<(f64, f64)> = (0..=100)
+        .map(|i| {
+            let angle = consts::PI * i as f64 / 100.0;
+            (500.0 * angle.sin(), 500.0 - 500.0 * angle.cos())
+        })
+        .collect();
+    let path = Path::from_points(&xy);
+    for point in path.points() {
+        assert!((point.curvature - 1.0 / 500.0).abs() < 1e-6);
+    }
+
+    let profile = PathProfile::new(&path, &model);
+    let limit = model.constraints(1.0 / 500.0).max_vel;
+    assert!(limit < model.lin_speed());
+    for state in profile.states() {
+        assert!(state.vel <= limit * (1.0 + 1e-9));
+    }
+    // the wheels stay within their own limits
+    for vels in profile.wheel_vels(&model) {
+        assert!(vels.left.abs().max(vels.right.abs()).as_mm_per_sec() <= 1500.0 + 1e-6);
+    }
+}
+
+#[test]
+fn trajectory_interpolates_between_points() {
+    let model = model();
+    let path = Path::from_points(&[(0.0, 0.0), (0.0, 300.0)]);
+    let trajectory = PathProfile::new(&path, &model).trajectory();
+    assert_eq!(
+        trajectory.duration(),
+        PathProfile::new(&path, &model).duration()
+    );
+
+    let mut prev = Length::ZERO;
+    let mut t = Duration::ZERO;
+    while t <= trajectory.duration() {
+        let state = trajectory.sample(t).unwrap();
+        assert!(state.pose.y >= prev && state.pose.x.as_mm().abs() < 1e-9);
+        assert!(state.pose.h.approx_eq(path.points()[0].pose.h, 1e-9));
+        prev = state.pose.y;
+        t += Duration::from_millis(5);
+    }
+    let end = trajectory.sample(trajectory.duration() * 2).unwrap();
+    assert!((end.pose.y.as_mm() - 300.0).abs() < 1e-9);
+}
+
+#[test]
+fn reversing_paths_stop_at_the_turn() {
+    let model = model();
+    let path = Path::from_points(&[(0.0, 0.0), (500.0, 0.0), (0.0, 0.0)]);
+    assert_eq!(path.points()[1].curvature, f64::INFINITY);
+    assert_eq!(path.points()[0].curvature, 0.0);
+
+    let profile = PathProfile::new(&path, &model);
+    for state in profile.states() {
+        assert!(state.vel.as_mm_per_sec().is_finite() && state.accel.as_mm_per_sec2().is_finite());
+    }
+    let turn = profile
+        .states()
+        .iter()
+        .find(|state| state.pos.as_mm() == 500.0)
+        .unwrap();
+    assert_eq!(turn.vel, LinearVelocity::ZERO);
+
+    // there and back, stopping in between
+    let one_way = TrapezoidProfile::new(Length::from_mm(500.0), &model.constraints(0.0));
+    let duration = profile.duration().as_secs_f64();
+    let expected = 2.0 * one_way.duration().as_secs_f64();
+    assert!(duration >= 0.98 * expected && duration < expected + 0.5);
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use lib3818_rs::pid::{Pid, Settle};
+
+mod common;
+
+use common::close;
+
+#[test]
+fn plain_pid() {
+    let mut pid = Pid::new(2.0, 0.5, 0.25);
+    // no derivative on the first update
+    assert!(close(pid.update(1.0, 0.1), 2.0 + 0.5 * 0.1));
+    assert!(close(pid.update(3.0, 0.1), 6.0 + 0.5 * 0.4 + 0.25 * 20.0));
+    pid.reset();
+    assert!(close(pid.update(1.0, 0.1), 2.0 + 0.5 * 0.1));
+}
+
+#[test]
+fn saturated_output_does_not_wind_up() {
+    let mut pid = Pid::new(0.0, 1.0, 0.0);
+    pid.set_output_limit(1.0);
+    for _ in 0..100 {
+        assert!(pid.update(10.0, 0.1) <= 1.0);
+    }
+    // unwinding 100 would take as long again, instead it drops straight away
+    assert!(close(pid.update(-0.5, 0.1), 0.95));
+}
+
+#[test]
+fn integral_term_is_clamped() {
+    let mut pid = Pid::new(1.0, 1.0, 0.0);
+    pid.set_integral_limit(0.5);
+    for _ in 0..100 {
+        pid.update(1.0, 0.1);
+    }
+    assert!(close(pid.update(1.0, 0.1), 1.5));
+    // the stored integral matches the clamped term, so it unwinds at once
+    assert!(close(pid.update(-1.0, 0.1), -1.0 + 0.4));
+}
+
+#[test]
+fn integral_resets_when_the_error_changes_sign() {
+    let mut resetting = Pid::new(0.0, 1.0, 0.0);
+    resetting.set_reset_on_sign_change(true);
+    let mut plain = Pid::new(0.0, 1.0, 0.0);
+    for _ in 0..3 {
+        resetting.update(1.0, 1.0);
+        plain.update(1.0, 1.0);
+    }
+    assert!(close(resetting.update(-1.0, 1.0), -1.0));
+    assert!(close(plain.update(-1.0, 1.0), 2.0));
+}
+
+#[test]
+fn derivative_is_low_pass_filtered() {
+    let mut pid = Pid::new(0.0, 0.0, 1.0);
+    pid.set_derivative_filter(0.5);
+    pid.update(0.0, 1.0);
+    // a step of 1 is spread over following updates
+    assert!(close(pid.update(1.0, 1.0), 0.5));
+    assert!(close(pid.update(1.0, 1.0), 0.25));
+    assert!(close(pid.update(1.0, 1.0), 0.125));
+}
+
+#[test]
+fn derivative_filter_stays_below_one() {
+    let mut pid = Pid::new(0.0, 0.0, 1.0);
+    pid.set_derivative_filter(1.0);
+    pid.update(0.0, 1.0);
+    // a steady ramp still produces a derivative
+    let output = (1..=1000)
+        .map(|i| pid.update(i as f64, 1.0))
+        .last()
+        .unwrap();
+    assert!(output > 0.99);
+}
+
+#[test]
+fn derivative_on_measurement_ignores_target_changes() {
+    let mut pid = Pid::new(0.0, 0.0, 1.0);
+    pid.set_derivative_on_measurement(true);
+    pid.update_measured(0.0, 0.0, 0.1);
+    // no kick when the target jumps
+    assert!(close(pid.update_measured(10.0, 0.0, 0.1), 0.0));
+    // moving towards the target is damped
+    assert!(close(pid.update_measured(10.0, 1.0, 0.1), -10.0));
+
+    let mut on_error = Pid::new(0.0, 0.0, 1.0);
+    on_error.update_measured(0.0, 0.0, 0.1);
+    assert!(close(on_error.update_measured(10.0, 0.0, 0.1), 100.0));
+}
+
+#[test]
+fn settles_after_the_window() {
+    let mut settle = Settle::new(1.0, 1.0, Duration::from_millis(200));
+    for _ in 0..3 {
+        assert!(!settle.update(0.5, 0.05));
+    }
+    assert!(settle.update(0.5, 0.05));
+    assert!(settle.settled());
+
+    // a large error starts the window again
+    assert!(!settle.update(2.0, 0.05));
+    settle.reset();
+    assert!(!settle.settled());
+}
+
+#[test]
+fn fast_errors_do_not_settle() {
+    let mut settle = Settle::new(1.0, 1.0, Duration::from_millis(200));
+    // small, but crossing zero at 20 per second
+    for i in 0..100 {
+        let error = if i % 2 == 0 { 0.5 } else { -0.5 };
+        assert!(!settle.update(error, 0.05));
+    }
+}
@@ -0,0 +1,103 @@
+use std::f64::consts;
+
+use lib3818_rs::{
+    localisation::{AngleSystem, Heading, Pose, PoseTwist},
+    units::{Angle, Length},
+};
+
+mod common;
+
+use common::{close_length, Rng, CASES};
+
+fn random_mm(rng: &mut Rng, max: f64) -> Length {
+    Length::from_mm(rng.uniform(-max, max))
+}
+
+fn random_pose(rng: &mut Rng) -> Pose {
+    Pose::new(
+        random_mm(rng, 2000.0),
+        random_mm(rng, 2000.0),
+        Heading::new(rng.uniform(-10.0, 10.0)),
+    )
+}
+
+fn pose(x: f64, y: f64, h: f64) -> Pose {
+    Pose::new(Length::from_mm(x), Length::from_mm(y), Heading::new(h))
+}
+
+fn twist(dx: f64, dy: f64, d_theta: f64) -> PoseTwist {
+    PoseTwist::new(
+        Length::from_mm(dx),
+        Length::from_mm(dy),
+        Angle::from_rad(d_theta),
+    )
+}
+
+fn assert_pose_eq(a: Pose, b: Pose) {
+    assert!(close_length(a.x, b.x), "{a} != {b}");
+    assert!(close_length(a.y, b.y), "{a} != {b}");
+    assert!(a.h.approx_eq(b.h, 1e-9), "{a} != {b}");
+}
+
+#[test]
+fn group_laws() {
+    let mut rng = Rng(0x3818);
+    for _ in 0..CASES {
+        let (a, b, c) = (
+            random_pose(&mut rng),
+            random_pose(&mut rng),
+            random_pose(&mut rng),
+        );
+        assert_pose_eq(a.compose(a.inverse()), Pose::identity());
+        assert_pose_eq(a.inverse().compose(a), Pose::identity());
+        assert_pose_eq(a.compose(Pose::identity()), a);
+        assert_pose_eq(a.compose(b).compose(c), a.compose(b.compose(c)));
+        // relative poses compose back onto their base
+        assert_pose_eq(a.compose(b.relative_to(a)), b);
+    }
+}
+
+#[test]
+fn transforms_points_both_ways() {
+    let facing_y = Pose::new(
+        Length::from_mm(100.0),
+        Length::from_mm(200.0),
+        Heading::from_deg(90.0, AngleSystem::Cartesian),
+    );
+    // a point ahead of a pose facing +y
+    let (x, y) = facing_y.transform_point((Length::from_mm(10.0), Length::ZERO));
+    assert!(close_length(x, Length::from_mm(100.0)) && close_length(y, Length::from_mm(210.0)));
+
+    let mut rng = Rng(0x1234);
+    for _ in 0..CASES {
+        let pose = random_pose(&mut rng);
+        let point = (random_mm(&mut rng, 500.0), random_mm(&mut rng, 500.0));
+        let (x, y) = pose.inverse_transform_point(pose.transform_point(point));
+        assert!(close_length(x, point.0) && close_length(y, point.1));
+    }
+}
+
+#[test]
+fn exp_follows_an_arc() {
+    // a quarter turn left around a 100mm radius ends 100mm ahead and 100mm left
+    let end = Pose::exp(twist(100.0 * consts::FRAC_PI_2, 0.0, consts::FRAC_PI_2));
+    assert_pose_eq(end, pose(100.0, 100.0, consts::FRAC_PI_2));
+    // straight lines, including through the series
+    assert_pose_eq(Pose::exp(twist(50.0, -20.0, 0.0)), pose(50.0, -20.0, 0.0));
+    assert_pose_eq(Pose::exp(twist(50.0, 0.0, 1e-12)), pose(50.0, 0.0, 1e-12));
+
+    let mut rng = Rng(0xbeef);
+    for _ in 0..CASES {
+        let expected = PoseTwist::new(
+            random_mm(&mut rng, 1000.0),
+            random_mm(&mut rng, 1000.0),
+            Angle::from_rad(rng.uniform(-3.0, 3.0)),
+        );
+        let log = Pose::exp(expected).log();
+        assert!(close_length(log.dx, expected.dx) && close_length(log.dy, expected.dy));
+        assert!((log.d_theta - expected.d_theta).abs().as_rad() < 1e-9);
+
+        let pose = random_pose(&mut rng);
+        assert_pose_eq(Pose::exp(pose.log()), pose);
+    }
+}
@@ -0,0 +1,71 @@
+use std::f64::consts;
+
+use lib3818_rs::{
+    localisation::{Heading, Pose, PoseTwist},
+    tank_chassis::pure_pursuit::PurePursuit,
+    units::{Angle, Length},
+};
+
+fn pose(x: f64, y: f64, h: f64) -> Pose {
+    Pose::new(Length::from_mm(x), Length::from_mm(y), Heading::new(h))
+}
+
+// drives `dist` along an arc of `curvature`
+fn drive(pose: Pose, curvature: f64, dist: f64) -> Pose {
+    pose.compose(Pose::exp(PoseTwist::new(
+        Length::from_mm(dist),
+        Length::ZERO,
+        Angle::from_rad(curvature * dist),
+    )))
+}
+
+#[test]
+fn steers_towards_the_lookahead_point() {
+    let path = vec![(0.0, 0.0), (0.0, 1000.0)];
+
+    // on the path, the lookahead point is straight ahead
+    let mut pursuit = PurePursuit::new(path.clone(), 200.0, 10.0);
+    let curvature = pursuit.update(pose(0.0, 0.0, consts::FRAC_PI_2)).unwrap();
+    assert!(curvature.abs() < 1e-12);
+
+    // 100 mm to the right of the path, the lookahead point is 100 mm to the left
+    let mut pursuit = PurePursuit::new(path.clone(), 200.0, 10.0);
+    let curvature = pursuit.update(pose(100.0, 0.0, consts::FRAC_PI_2)).unwrap();
+    assert!((curvature - 2.0 * 100.0 / (200.0 * 200.0)).abs() < 1e-12);
+
+    // backwards, the robot drives with negative speed so curvature flips
+    let mut pursuit = PurePursuit::new(path, 200.0, 10.0);
+    pursuit.set_reversed(true);
+    let curvature = pursuit
+        .update(pose(100.0, 0.0, -consts::FRAC_PI_2))
+        .unwrap();
+    assert!((curvature + 2.0 * 100.0 / (200.0 * 200.0)).abs() < 1e-12);
+}
+
+#[test]
+fn does_not_skip_to_a_nearby_later_segment() {
+    // up, across and back down 100 mm to the right
+    let path = vec![(0.0, 0.0), (0.0, 1000.0), (100.0, 1000.0), (100.0, 0.0)];
+    let mut pursuit = PurePursuit::new(path, 150.0, 10.0);
+    // the way back is within the lookahead, but 1800 mm further along the path
+    let curvature = pursuit.update(pose(0.0, 100.0, consts::FRAC_PI_2)).unwrap();
+    assert!(curvature.abs() < 1e-12);
+}
+
+#[test]
+fn finishes_at_the_last_waypoint() {
+    let path = vec![(0.0, 0.0), (0.0, 600.0), (600.0, 600.0), (600.0, 0.0)];
+    let mut pursuit = PurePursuit::new(path, 150.0, 10.0);
+
+    let mut robot = pose(0.0, 0.0, consts::FRAC_PI_2);
+    let mut steps = 0;
+    while let Some(curvature) = pursuit.update(robot) {
+        robot = drive(robot, curvature, 5.0);
+        steps += 1;
+        assert!(steps < 1000, "never finished, at {robot}");
+    }
+    assert!(pursuit.finished(robot));
+    assert!((robot.x.as_mm() - 600.0).hypot(robot.y.as_mm()) < 10.0);
+    // further updates stay finished
+    assert!(pursuit.update(robot).is_none());
+}
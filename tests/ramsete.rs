@@ -0,0 +1,77 @@
+use std::{f64::consts, time::Duration};
+
+use lib3818_rs::{
+    localisation::{Heading, Pose},
+    tank_chassis::{path::TrajectoryState, ramsete::Ramsete},
+    units::{AngularVelocity, Length, LinearVelocity},
+};
+
+mod common;
+
+use common::close;
+
+const B: f64 = 2e-6;
+const ZETA: f64 = 0.7;
+
+fn pose(x: f64, y: f64, h: f64) -> Pose {
+    Pose::new(Length::from_mm(x), Length::from_mm(y), Heading::new(h))
+}
+
+// (linear, angular) velocity in mm/s and rad/s
+fn calculate(ramsete: &Ramsete, target: &TrajectoryState, robot: Pose) -> (f64, f64) {
+    let twist = ramsete.calculate(target, robot);
+    (twist.linear.as_mm_per_sec(), twist.angular.as_rad_per_sec())
+}
+
+fn target(pose: Pose, lin_vel: f64, ang_vel: f64) -> TrajectoryState {
+    TrajectoryState {
+        t: Duration::ZERO,
+        pose,
+        lin_vel: LinearVelocity::from_mm_per_sec(lin_vel),
+        ang_vel: AngularVelocity::from_rad_per_sec(ang_vel),
+    }
+}
+
+#[test]
+fn no_error_passes_the_target_through() {
+    let ramsete = Ramsete::new(B, ZETA);
+    let robot = pose(300.0, -200.0, 1.0);
+    let (v, w) = calculate(&ramsete, &target(robot, 800.0, 1.5), robot);
+    assert!(close(v, 800.0));
+    assert!(close(w, 1.5));
+}
+
+#[test]
+fn corrects_known_errors() {
+    let ramsete = Ramsete::new(B, ZETA);
+    // facing +y, so the robot frame's forward is +y and left is -x
+    let robot = pose(0.0, 0.0, consts::FRAC_PI_2);
+    let k = 2.0 * ZETA * (B * 1000.0 * 1000.0).sqrt();
+
+    // 10 mm ahead speeds up
+    let (v, w) = calculate(
+        &ramsete,
+        &target(pose(0.0, 10.0, consts::FRAC_PI_2), 1000.0, 0.0),
+        robot,
+    );
+    assert!(close(v, 1000.0 + k * 10.0));
+    assert!(close(w, 0.0));
+
+    // 10 mm to the left turns left
+    let (v, w) = calculate(
+        &ramsete,
+        &target(pose(-10.0, 0.0, consts::FRAC_PI_2), 1000.0, 0.0),
+        robot,
+    );
+    assert!(close(v, 1000.0));
+    assert!(close(w, B * 1000.0 * 10.0));
+
+    // facing 0.1 rad further counterclockwise slows down and turns left
+    let (v, w) = calculate(
+        &ramsete,
+        &target(pose(0.0, 0.0, consts::FRAC_PI_2 + 0.1), 1000.0, 0.0),
+        robot,
+    );
+    assert!(close(v, 1000.0 * 0.1_f64.cos()));
+    assert!(close(w, k * 0.1));
+}
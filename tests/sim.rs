@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use lib3818_rs::{
+    localisation::{
+        shared::{LocalisationRunner, SharedPose},
+        AngleSystem, Heading, Localiser, Pose,
+    },
+    pid::{Pid, Settle},
+    rt::{advance, sleep_until, Instant},
+    sim::TankSim,
+    tank_chassis::{
+        boomerang::MoveToPose,
+        commands::{ChassisCommands, CommandSettings, TurnDirection},
+    },
+    units::Length,
+};
+
+mod common;
+
+use common::{lin_speed, robot, Odometry, Robot, CLOCK};
+
+fn run_for(sim: &mut TankSim, duration: Duration) {
+    advance(duration);
+    sim.update();
+}
+
+fn run_steps(sim: &mut TankSim, runner: &mut LocalisationRunner<Odometry>, steps: usize) {
+    sim.block_on(async {
+        for _ in 0..steps {
+            let time_start = Instant::now();
+            runner.step().await;
+            sleep_until(time_start + Duration::from_millis(10)).await;
+        }
+    });
+}
+
+#[test]
+fn full_voltage_reaches_lin_speed() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    robot
+        .devices
+        .chassis
+        .try_lock()
+        .unwrap()
+        .move_tank(1.0, 1.0);
+    run_for(&mut robot.sim, Duration::from_secs(3));
+
+    let pose = robot.sim.pose();
+    let lin_speed = lin_speed().as_mm_per_sec();
+    assert!((robot.sim.lin_vel() - lin_speed).abs() < 0.01 * lin_speed);
+    assert!(pose.x.as_mm() > lin_speed);
+    assert!(pose.y.as_mm().abs() < 1e-6);
+    assert!(robot.sim.ang_vel().abs() < 1e-9);
+}
+
+#[test]
+fn opposite_voltages_turn_in_place() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    robot
+        .devices
+        .chassis
+        .try_lock()
+        .unwrap()
+        .move_tank(-0.5, 0.5);
+    run_for(&mut robot.sim, Duration::from_millis(500));
+
+    let pose = robot.sim.pose();
+    assert!(robot.sim.ang_vel() > 0.0);
+    assert!(pose.h.as_rad(AngleSystem::Cartesian) > 0.0);
+    assert!(pose.x.as_mm().abs() < 1e-6 && pose.y.as_mm().abs() < 1e-6);
+}
+
+#[test]
+fn brake_stops_the_robot() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    robot
+        .devices
+        .chassis
+        .try_lock()
+        .unwrap()
+        .move_tank(1.0, 1.0);
+    run_for(&mut robot.sim, Duration::from_secs(1));
+    robot
+        .devices
+        .chassis
+        .try_lock()
+        .unwrap()
+        .brake(lib3818_rs::hal::BrakeMode::Brake);
+    run_for(&mut robot.sim, Duration::from_secs(1));
+
+    assert!(robot.sim.lin_vel().abs() < 1.0);
+}
+
+#[test]
+fn odometry_follows_simulated_pose() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    let chassis = robot.devices.chassis.clone();
+    let localiser = &mut robot.localiser;
+    robot.sim.block_on(async {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(1) {
+            let time_start = Instant::now();
+            localiser.update().await;
+            chassis.lock().await.move_tank(0.4, 0.8);
+            sleep_until(time_start + Duration::from_millis(10)).await;
+        }
+        localiser.update().await;
+    });
+
+    let expected = robot.sim.pose();
+    let pose = robot.localiser.pose();
+    assert!((pose.x - expected.x).hypot(pose.y - expected.y).as_mm() < 5.0);
+    let h_error = pose.h.as_rad(AngleSystem::Cartesian) - expected.h.as_rad(AngleSystem::Cartesian);
+    assert!(h_error.abs() < 0.5_f64.to_radians());
+    // curved to the left
+    assert!(expected.y.as_mm() > 100.0 && expected.h.as_rad(AngleSystem::Cartesian) > 0.0);
+}
+
+#[test]
+fn shared_pose_publishes_runner_updates() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let Robot {
+        mut sim,
+        devices,
+        localiser,
+    } = robot();
+    let chassis = devices.chassis;
+    let mut runner = LocalisationRunner::new(localiser, Duration::from_millis(10));
+    let pose = runner.handle();
+    let other = pose.clone();
+
+    // shared between tasks on any thread
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedPose>();
+
+    chassis.try_lock().unwrap().move_tank(0.5, 0.5);
+    run_steps(&mut sim, &mut runner, 50);
+    // within a period's travel, the sim moves after each update
+    let near = |a: Pose, b: Pose| (a.x - b.x).hypot(a.y - b.y).as_mm() < 10.0;
+    let truth = sim.pose();
+    assert!(near(sim.block_on(pose.pose()), truth) && near(sim.block_on(other.pose()), truth));
+
+    // paused, the pose holds until resumed and then catches up
+    sim.block_on(other.pause());
+    let held = sim.block_on(pose.pose());
+    run_steps(&mut sim, &mut runner, 50);
+    assert_eq!(sim.block_on(pose.pose()).y, held.y);
+    sim.block_on(other.resume());
+    run_steps(&mut sim, &mut runner, 1);
+    let truth = sim.pose();
+    assert!(near(sim.block_on(pose.pose()), truth));
+
+    // as a localiser, the handle fetches the published pose on update
+    let mut follower = pose.clone();
+    sim.block_on(follower.update());
+    assert!(near(Localiser::pose(&follower), truth));
+
+    // a reset from any holder is published at once and kept by the localiser
+    chassis
+        .try_lock()
+        .unwrap()
+        .brake(lib3818_rs::hal::BrakeMode::Brake);
+    run_steps(&mut sim, &mut runner, 100);
+    sim.block_on(other.set_pose(Pose::identity()));
+    assert_eq!(sim.block_on(pose.pose()).y, Length::ZERO);
+    run_steps(&mut sim, &mut runner, 10);
+    let reset = sim.block_on(pose.pose());
+    assert!(reset.x.hypot(reset.y).as_mm() < 1.0);
+
+    // and through the localiser interface, on the holder's next update
+    let moved = Pose::new(Length::from_mm(100.0), Length::ZERO, Heading::new(0.0));
+    Localiser::set_pose(&mut follower, moved);
+    assert_eq!(sim.block_on(pose.pose()).x, reset.x);
+    sim.block_on(follower.update());
+    assert_eq!(sim.block_on(pose.pose()).x, moved.x);
+}
+
+#[test]
+fn drive_distance_settles_at_target() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    let mut settings = CommandSettings::new(
+        // the simulated drive has no friction, so needs more damping than the robot
+        Pid::new(0.005, 0.0, 0.0012),
+        Settle::new(10.0, 20.0, Duration::from_millis(200)),
+        Duration::from_secs(3),
+    );
+    let chassis = robot.devices.chassis.clone();
+    let settled =
+        robot
+            .sim
+            .block_on(chassis.drive_distance(&mut robot.localiser, 600.0, &mut settings));
+
+    assert!(settled);
+    let pose = robot.sim.pose();
+    assert!((pose.x.as_mm() - 600.0).abs() < 15.0);
+    assert!(pose.y.as_mm().abs() < 5.0);
+}
+
+#[test]
+fn move_to_pose_settles_at_target() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    let target = Pose::new(
+        Length::from_mm(800.0),
+        Length::from_mm(300.0),
+        Heading::new(0.0),
+    );
+    let mut controller = MoveToPose::new(
+        target,
+        Pid::new(0.005, 0.0, 0.0012),
+        Pid::new(1.0, 0.0, 0.08),
+    );
+    let chassis = robot.devices.chassis.clone();
+    let settled = robot
+        .sim
+        .block_on(controller.run(&chassis, &mut robot.localiser));
+
+    assert!(settled);
+    let pose = robot.sim.pose();
+    assert!((pose.x - target.x).hypot(pose.y - target.y).as_mm() < 15.0);
+    assert!(pose.h.approx_eq(target.h, 3.0_f64.to_radians()));
+}
+
+#[test]
+fn turn_to_heading_turns_the_forced_way() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut robot = robot();
+    let mut settings = CommandSettings::new(
+        Pid::new(0.8, 0.0, 0.1),
+        Settle::new(
+            1.5_f64.to_radians(),
+            10.0_f64.to_radians(),
+            Duration::from_millis(200),
+        ),
+        Duration::from_millis(300),
+    );
+    let chassis = robot.devices.chassis.clone();
+    // 45 degrees counterclockwise, but the long way round
+    let target = Heading::from_deg(45.0, AngleSystem::Cartesian);
+    robot.sim.block_on(chassis.turn_to_heading(
+        &mut robot.localiser,
+        target,
+        TurnDirection::Clockwise,
+        &mut settings,
+    ));
+    assert!(robot.sim.pose().h.as_rad(AngleSystem::Cartesian) < -0.2);
+
+    settings.timeout = Duration::from_secs(4);
+    let settled = robot.sim.block_on(chassis.turn_to_heading(
+        &mut robot.localiser,
+        target,
+        TurnDirection::Clockwise,
+        &mut settings,
+    ));
+    assert!(settled);
+    assert!(robot.sim.pose().h.approx_eq(target, 2.0_f64.to_radians()));
+}
@@ -0,0 +1,164 @@
+// the mocks share state through `Rc`, the runtime is single threaded anyway
+#![allow(clippy::arc_with_non_send_sync)]
+
+use std::sync::Arc;
+
+use lib3818_rs::{
+    hal::mock::MockMotor,
+    localisation::{
+        measurement::{DistanceMeasurement, ImuHeadingMeasurement},
+        ukf::{DriveSideMeasurement, TrackerMeasurement, UkfLocaliser, UkfMeasurement},
+        AngleSystem, ChassisSide, Heading, Localiser, Pose, TrackerDirection,
+    },
+    rt::Mutex,
+    tank_chassis::TankChassis,
+    units::{Length, LinearVelocity},
+};
+
+mod common;
+
+use common::{dist, Robot, CLOCK, TRACKING_WHEEL_DIAMETER, TRACK_WIDTH, WHEEL_DIAMETER};
+
+fn robot(truth: Pose, belief: Pose, use_sensors: bool) -> Robot<UkfLocaliser> {
+    Robot::new(truth, belief, |devices| {
+        let mut measurements: Vec<Box<dyn UkfMeasurement>> = vec![
+            Box::new(TrackerMeasurement::new(
+                devices.odom_x.clone(),
+                TrackerDirection::Lateral,
+                Length::ZERO,
+                TRACKING_WHEEL_DIAMETER,
+            )),
+            Box::new(DriveSideMeasurement::new(
+                devices.chassis.clone(),
+                ChassisSide::Left,
+                TRACK_WIDTH,
+                WHEEL_DIAMETER,
+            )),
+            Box::new(DriveSideMeasurement::new(
+                devices.chassis.clone(),
+                ChassisSide::Right,
+                TRACK_WIDTH,
+                WHEEL_DIAMETER,
+            )),
+        ];
+        if use_sensors {
+            measurements.push(Box::new(ImuHeadingMeasurement::new(devices.imu.clone())));
+            for (sensor, mount) in devices.sensors.iter() {
+                measurements.push(Box::new(DistanceMeasurement::new(
+                    sensor.clone(),
+                    *mount,
+                    devices.field.clone(),
+                )));
+            }
+        }
+        let mut localiser = UkfLocaliser::new(measurements, belief);
+        localiser.set_spread(80.0, 2.0_f64.to_radians());
+        localiser
+    })
+}
+
+fn heading_error(a: Pose, b: Pose) -> f64 {
+    a.h.diff(b.h)
+}
+
+#[test]
+fn tracks_wheels_alone() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(1800.0),
+        Length::from_mm(1800.0),
+        Heading::new(0.3),
+    );
+    let mut robot = robot(truth, truth, false);
+
+    for _ in 0..100 {
+        robot.tick(0.4, 0.25);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let truth = robot.sim.pose();
+    let estimate = robot.localiser.pose();
+    assert!(dist(estimate, truth) < 15.0);
+    assert!(heading_error(estimate, truth).abs() < 2.0_f64.to_radians());
+    assert!(robot.localiser.twist().linear > LinearVelocity::from_mm_per_sec(100.0));
+}
+
+#[test]
+fn converges_with_walls() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(2800.0),
+        Length::from_mm(600.0),
+        Heading::new(0.0),
+    );
+    let belief = Pose::new(
+        Length::from_mm(2860.0),
+        Length::from_mm(550.0),
+        Heading::new(0.0),
+    );
+    let mut robot = robot(truth, belief, true);
+
+    for _ in 0..100 {
+        robot.tick(0.25, 0.25);
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let truth = robot.sim.pose();
+    let estimate = robot.localiser.pose();
+    assert!(dist(estimate, truth) < 20.0);
+    assert!(heading_error(estimate, truth).abs() < 1.0_f64.to_radians());
+    // the filter is more certain than when it started
+    let covariance = robot.localiser.covariance();
+    assert!(covariance[(0, 0)] < 80.0 * 80.0 && covariance[(1, 1)] < 80.0 * 80.0);
+}
+
+#[test]
+fn heading_wraps_across_pi() {
+    let _clock = CLOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let truth = Pose::new(
+        Length::from_mm(1800.0),
+        Length::from_mm(1800.0),
+        Heading::from_deg(170.0, AngleSystem::Cartesian),
+    );
+    let mut robot = robot(truth, truth, true);
+
+    // turn left in place, past 180 degrees
+    for _ in 0..100 {
+        robot.tick(-0.3, 0.3);
+        let error = heading_error(robot.localiser.pose(), robot.sim.pose());
+        assert!(error.abs() < 2.0_f64.to_radians());
+    }
+    robot.sim.block_on(robot.localiser.update());
+
+    let heading = robot.sim.pose().h.as_deg(AngleSystem::Cartesian);
+    assert!(heading > 200.0 || (-160.0..0.0).contains(&heading));
+    assert!(dist(robot.localiser.pose(), robot.sim.pose()) < 15.0);
+}
+
+#[test]
+fn drive_travel_restarts_after_a_locked_read() {
+    let left = vec![MockMotor::new(600.0)];
+    let chassis = Arc::new(Mutex::new(TankChassis::new(
+        left.clone(),
+        vec![MockMotor::new(600.0)],
+    )));
+    let mut side = DriveSideMeasurement::new(
+        chassis.clone(),
+        ChassisSide::Left,
+        TRACK_WIDTH,
+        WHEEL_DIAMETER,
+    );
+    assert_eq!(side.read(), None);
+    left[0].set_measured(360.0, 0.0);
+    let one_turn = side.read().unwrap();
+
+    // the turn made while the chassis is locked isn't reported with the next one
+    {
+        let _chassis = chassis.try_lock().unwrap();
+        left[0].set_measured(720.0, 0.0);
+        assert_eq!(side.read(), None);
+    }
+    assert_eq!(side.read(), None);
+    left[0].set_measured(1080.0, 0.0);
+    assert!((side.read().unwrap() - one_turn).abs() < 1e-9);
+}
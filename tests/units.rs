@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use lib3818_rs::{
+    tank_chassis::mp::{MotionProfile, TankConstraints, TrapezoidProfile},
+    units::{Angle, AngularVelocity, Length, LinearAcceleration, LinearJerk, LinearVelocity},
+};
+
+mod common;
+
+use common::close;
+
+#[test]
+fn converts_between_units() {
+    assert!(close(Length::from_inches(1.0).as_mm(), 25.4));
+    assert!(close(Length::from_tiles(6.0).as_mm(), 3657.6));
+    assert!(close(Length::from_mm(609.6).as_tiles(), 1.0));
+    assert!(close(Angle::from_rotations(0.5).as_deg(), 180.0));
+    assert!(close(
+        Angle::from_deg(90.0).as_rad(),
+        std::f64::consts::FRAC_PI_2
+    ));
+    assert!(close(
+        AngularVelocity::from_rpm(60.0).as_deg_per_sec(),
+        360.0
+    ));
+    assert!(close(
+        LinearVelocity::from_tiles_per_sec(1.0).as_inches_per_sec(),
+        24.0
+    ));
+
+    // a 2 inch wheel rolls its circumference in a full turn
+    let rolled = Angle::from_rotations(1.0).arc_length(Length::from_inches(2.0));
+    assert!(close(rolled.as_inches(), 2.0 * std::f64::consts::PI));
+}
+
+#[test]
+fn time_divides_into_rates() {
+    let second = Duration::from_secs(1);
+    let vel = Length::from_mm(500.0) / Duration::from_millis(250);
+    assert!(close(vel.as_mm_per_sec(), 2000.0));
+    assert!(close((vel * second).as_mm(), 2000.0));
+    assert!(close((vel / second).as_mm_per_sec2(), 2000.0));
+    assert!(close(
+        (Angle::from_deg(90.0) / second).as_deg_per_sec(),
+        90.0
+    ));
+    assert!(close(Length::from_mm(300.0) / Length::from_mm(200.0), 1.5));
+}
+
+#[test]
+fn profiles_use_units() {
+    let constraints = TankConstraints {
+        max_vel: LinearVelocity::from_mm_per_sec(1000.0),
+        max_accel: LinearAcceleration::from_mm_per_sec2(2000.0),
+        max_jerk: LinearJerk::INFINITY,
+    };
+    // half a second each to accelerate and decelerate, a second cruising
+    let profile = TrapezoidProfile::new(Length::from_mm(1500.0), &constraints);
+    assert!(close(profile.duration().as_secs_f64(), 2.0));
+
+    let cruise = profile.sample(Duration::from_secs(1));
+    assert!(close(cruise.pos.as_mm(), 750.0));
+    assert!(close(cruise.vel.as_mm_per_sec(), 1000.0));
+
+    let states = profile.samples(Duration::from_millis(300));
+    assert_eq!(states.len(), 8);
+    let end = states[states.len() - 1];
+    assert_eq!(end.t, profile.duration());
+    assert!(close(end.pos.as_mm(), 1500.0));
+    assert_eq!(end.vel, LinearVelocity::ZERO);
+}
@@ -0,0 +1,129 @@
+use std::f64::consts;
+
+use lib3818_rs::{
+    hal::{mock::MockDistanceSensor, DistanceReading},
+    localisation::{
+        field::{Field, SensorMount},
+        wall_reset::WallReset,
+        AngleSystem, Heading, Pose,
+    },
+    units::Length,
+};
+
+mod common;
+
+use common::close_length;
+
+fn reading(distance: f64) -> Option<DistanceReading> {
+    Some(DistanceReading {
+        distance,
+        confidence: 1.0,
+        relative_size: 200,
+    })
+}
+
+#[test]
+fn snaps_each_coordinate_to_the_wall_it_faces() {
+    let reset = WallReset::new(Field::perimeter((0.0, 0.0)));
+    // one facing right, one facing backwards
+    let right = MockDistanceSensor::new();
+    let back = MockDistanceSensor::new();
+    let sensors = [
+        (&right, SensorMount::new(100.0, 0.0, -consts::FRAC_PI_2)),
+        (&back, SensorMount::new(0.0, -100.0, consts::PI)),
+    ];
+
+    // facing +y, so right faces the x = 3657.6 wall and back faces y = 0
+    let truth = Pose::new(
+        Length::from_mm(3000.0),
+        Length::from_mm(400.0),
+        Heading::new(consts::FRAC_PI_2),
+    );
+    right.set_object(reading(3657.6 - 3000.0 - 100.0));
+    back.set_object(reading(400.0 - 100.0));
+
+    let drifted = Pose::new(Length::from_mm(3040.0), Length::from_mm(370.0), truth.h);
+    let pose = reset.correct(drifted, &sensors);
+    assert!(close_length(pose.x, truth.x));
+    assert!(close_length(pose.y, truth.y));
+    assert!(pose.h.approx_eq(truth.h, 1e-9));
+}
+
+#[test]
+fn ignores_untrustworthy_readings() {
+    let mut reset = WallReset::new(Field::perimeter((0.0, 0.0)));
+    reset.set_max_correction(100.0);
+    let sensor = MockDistanceSensor::new();
+    let sensors = [(&sensor, SensorMount::new(0.0, 0.0, 0.0))];
+    // facing +x, 600 mm from the wall
+    let pose = Pose::new(
+        Length::from_mm(3057.6),
+        Length::from_mm(1000.0),
+        Heading::new(0.0),
+    );
+
+    let unchanged = |reset: &WallReset| close_length(reset.correct(pose, &sensors).x, pose.x);
+
+    sensor.set_object(Some(DistanceReading {
+        distance: 580.0,
+        confidence: 0.3,
+        relative_size: 200,
+    }));
+    assert!(unchanged(&reset));
+
+    // small, probably another robot
+    sensor.set_object(Some(DistanceReading {
+        distance: 580.0,
+        confidence: 1.0,
+        relative_size: 10,
+    }));
+    assert!(unchanged(&reset));
+
+    // something in the way
+    sensor.set_object(reading(300.0));
+    assert!(unchanged(&reset));
+
+    sensor.set_object(None);
+    assert!(unchanged(&reset));
+
+    sensor.set_object(reading(580.0));
+    assert!(close_length(
+        reset.correct(pose, &sensors).x,
+        pose.x + Length::from_mm(20.0)
+    ));
+
+    // glancing off the wall
+    let angled = Pose::new(
+        pose.x,
+        pose.y,
+        Heading::from_deg(40.0, AngleSystem::Cartesian),
+    );
+    sensor.set_object(reading(580.0 / 40.0_f64.to_radians().cos()));
+    assert!(close_length(reset.correct(angled, &sensors).x, pose.x));
+}
+
+#[test]
+fn averages_sensors_on_the_same_wall() {
+    let reset = WallReset::new(Field::perimeter((0.0, 0.0)));
+    let a = MockDistanceSensor::new();
+    let b = MockDistanceSensor::new();
+    let sensors = vec![
+        (a.clone(), SensorMount::new(-100.0, 0.0, 0.0)),
+        (b.clone(), SensorMount::new(100.0, 0.0, 0.0)),
+    ];
+    // facing +y towards the far wall, slightly turned
+    let pose = Pose::new(
+        Length::from_mm(1800.0),
+        Length::from_mm(3000.0),
+        Heading::from_deg(95.0, AngleSystem::Cartesian),
+    );
+    let sin = 95.0_f64.to_radians().sin();
+    for ((sensor, mount), error) in sensors.iter().zip([10.0, 30.0]) {
+        let expected = mount.expected(pose, &Field::perimeter((0.0, 0.0))).unwrap();
+        sensor.set_object(reading(expected - error / sin));
+    }
+
+    let corrected = reset.correct(pose, &sensors);
+    assert!(close_length(corrected.y, pose.y + Length::from_mm(20.0)));
+    assert!(close_length(corrected.x, pose.x));
+}